@@ -1,6 +1,5 @@
-use crate::utils::num::Num;
+use crate::utils::num::Int;
 use crate::utils::tokens::*;
-use crate::FLOAT_PRECISION;
 
 pub fn to_ast(postfix: &[Token]) -> Vec<Node> {
     let mut output = Vec::with_capacity(postfix.len());
@@ -13,7 +12,11 @@ pub fn to_ast(postfix: &[Token]) -> Vec<Node> {
 
             Token::Number(num) => output.push(Node::Number(num.clone())),
 
-            Token::Variable(val) => output.push(Node::Variable(val.clone())),
+            Token::Integer(int) => output.push(Node::Integer(int.clone())),
+
+            Token::Complex(cpx) => output.push(Node::Complex(cpx.clone())),
+
+            Token::Variable(val, span) => output.push(Node::Variable(val.clone(), *span)),
 
             Token::Block(body, chr, nm) => {
                 output.push(match *chr {
@@ -23,8 +26,8 @@ pub fn to_ast(postfix: &[Token]) -> Vec<Node> {
                         let body = to_ast(body);
                         match body.last() {
                             // (Maybe) Sized Sequence
-                            Some(Node::Op(f, block_node, size_node)) if f == "->" => {
-                                if let Some(Node::Block(_, _)) = block_node.get(0) {
+                            Some(Node::Op(f, block_node, size_node, _)) if f == "->" => {
+                                if let Some(Node::Block(_, _)) = block_node.first() {
                                     let seq_body = &body[0..body.len() - 1];
                                     Node::Sequence(
                                         seq_body.to_owned(),
@@ -35,10 +38,7 @@ pub fn to_ast(postfix: &[Token]) -> Vec<Node> {
                                     Node::Sequence(
                                         body.clone(),
                                         Box::new(Node::Block(vec![], None)),
-                                        Some(Box::new(Node::Number(Num::with_val(
-                                            *FLOAT_PRECISION,
-                                            body.len(),
-                                        )))),
+                                        Some(Box::new(Node::Integer(Int::from(body.len())))),
                                     )
                                 }
                             }
@@ -57,18 +57,16 @@ pub fn to_ast(postfix: &[Token]) -> Vec<Node> {
                             _ => Node::Sequence(
                                 body.clone(),
                                 Box::new(Node::Block(vec![], None)),
-                                Some(Box::new(Node::Number(Num::with_val(
-                                    *FLOAT_PRECISION,
-                                    body.len(),
-                                )))),
+                                Some(Box::new(Node::Integer(Int::from(body.len())))),
                             ),
                         }
                     }
+                    '.' => Node::Map(to_ast(body)),
                     _ => unimplemented!(),
                 });
             }
 
-            Token::Operator(ident, rank) => {
+            Token::Operator(ident, rank, span) => {
                 let mut left = Vec::new();
                 let mut right = Vec::new();
 
@@ -77,7 +75,7 @@ pub fn to_ast(postfix: &[Token]) -> Vec<Node> {
                         0,
                         output
                             .pop()
-                            .unwrap_or_else(|| Node::Variable("_".to_string())),
+                            .unwrap_or_else(|| Node::Variable("_".to_string(), Span::default())),
                     );
                 }
 
@@ -86,14 +84,14 @@ pub fn to_ast(postfix: &[Token]) -> Vec<Node> {
                         0,
                         output
                             .pop()
-                            .unwrap_or_else(|| Node::Variable("_".to_string())),
+                            .unwrap_or_else(|| Node::Variable("_".to_string(), Span::default())),
                     );
                 }
 
-                output.push(Node::Op(ident.clone(), left, right));
+                output.push(Node::Op(ident.clone(), left, right, *span));
             }
 
-            _ => panic!("Error on token {:?}", tok),
+            Token::Comma => panic!("Error on token {:?}", tok),
         }
     }
 
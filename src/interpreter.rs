@@ -0,0 +1,400 @@
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use crate::parser::{self, parse_node};
+use crate::utils::error::{ArnError, ArnResult};
+use crate::utils::io::{Input, Output};
+use crate::utils::num::Int;
+use crate::utils::{self, env::Environment, tokens::Node, types::*};
+use crate::{Precision, RationalMode};
+
+lazy_static! {
+    static ref DEFAULT: Node = Node::String(String::new());
+}
+
+// Settings that would otherwise be passed on the command line
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// Precision of internal floats
+    pub float_precision: u32,
+    /// Precision of outputted numbers
+    pub output_precision: usize,
+    /// Whether division keeps exact fractions, and how they are printed (`-q`, `-Q`)
+    pub rational: RationalMode,
+    /// Value bound to `_`, unless `Interpreter::with_io` is given another input
+    pub stdin: String,
+    /// Interprets `stdin` as Arn code (`-e`)
+    pub eval: bool,
+
+    /// Sets STDIN to the range [1, 10] (`-d`)
+    pub one_ten: bool,
+    /// Sets STDIN to the range [1, 100] (`-h`)
+    pub one_hundred: bool,
+    /// Converts STDIN `r` to the range [1, r] (`-r`)
+    pub rangeify: bool,
+    /// Sets STDIN N to the range [0, N) (`-R`)
+    pub zero_range: bool,
+    /// Unpacks STDIN with left precedence (`-t`)
+    pub input_left: bool,
+    /// Unpacks STDIN with right precedence (`-T`)
+    pub input_right: bool,
+
+    /// Wraps program in `[ ... ]` (`-a`)
+    pub array: bool,
+    /// Executes the program like it's mapped over the input (`-m`)
+    pub map: bool,
+    /// Flattens returned value (`-F`)
+    pub flat: bool,
+    /// Gets the index of the input inside the return value of the program (`-I`)
+    pub find: bool,
+
+    /// Returns first value in return value of program (`-f`)
+    pub first: bool,
+    /// Returns last value in return value of program (`-l`)
+    pub last: bool,
+    /// Yields the i'th value in the return value of the program, where i is STDIN (`-i`)
+    pub index: bool,
+    /// Sums the returned value of the program (`-x`)
+    pub sum: bool,
+    /// Returns size of return value of program (`-s`)
+    pub size: bool,
+    /// Boolean nots the returned value (`-!`)
+    pub not: bool,
+}
+
+impl Options {
+    #[inline]
+    pub fn precision(&self) -> Precision {
+        Precision {
+            float: self.float_precision,
+            output: self.output_precision,
+        }
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            float_precision: Precision::default().float,
+            output_precision: Precision::default().output,
+            rational: RationalMode::default(),
+            stdin: String::new(),
+            eval: false,
+            one_ten: false,
+            one_hundred: false,
+            rangeify: false,
+            zero_range: false,
+            input_left: false,
+            input_right: false,
+            array: false,
+            map: false,
+            flat: false,
+            find: false,
+            first: false,
+            last: false,
+            index: false,
+            sum: false,
+            size: false,
+            not: false,
+        }
+    }
+}
+
+// Evaluates Arn programs against a persistent environment
+pub struct Interpreter {
+    options: Options,
+    // Text read from the input, before any of the STDIN flags are applied
+    input: String,
+    stdin: String,
+    output: Output,
+    env: Env,
+}
+
+// The settings of the current thread before an interpreter installed its own, put back when dropped
+// so values are not printed with the precision of whichever interpreter ran last
+struct Installed {
+    precision: Precision,
+    rational: RationalMode,
+}
+
+impl Installed {
+    fn new(precision: Precision, rational: RationalMode) -> Self {
+        let prev = Self {
+            precision: crate::precision(),
+            rational: crate::rational_mode(),
+        };
+        crate::set_precision(precision);
+        crate::set_rational_mode(rational);
+        prev
+    }
+}
+
+impl Drop for Installed {
+    fn drop(&mut self) {
+        crate::set_precision(self.precision);
+        crate::set_rational_mode(self.rational);
+    }
+}
+
+// <precision> if rug can make floats that precise
+fn check_precision(precision: Precision) -> ArnResult<Precision> {
+    let bits = precision.float;
+    if bits < rug::float::prec_min() || bits > rug::float::prec_max() {
+        return Err(ArnError::Input(format!(
+            "`{bits}` is not a valid float precision"
+        )));
+    }
+    Ok(precision)
+}
+
+impl Interpreter {
+    // Binds `options.stdin` to `_` and prints to STDOUT
+    pub fn new(options: Options) -> ArnResult<Self> {
+        let input = options.stdin.clone();
+        Self::with_io(options, input, io::stdout())
+    }
+
+    // Binds the text read from <input> to `_`, and sends everything the program prints to <output>
+    pub fn with_io(
+        options: Options,
+        mut input: impl Input,
+        output: impl Write + 'static,
+    ) -> ArnResult<Self> {
+        let input = input
+            .read_input()
+            .map_err(|e| ArnError::Io(e.to_string()))?;
+        Self::build(options, input, Rc::new(RefCell::new(output)))
+    }
+
+    fn build(options: Options, input: String, output: Output) -> ArnResult<Self> {
+        let precision = check_precision(options.precision())?;
+        // Constants are created with the interpreter's precision
+        let _installed = Installed::new(precision, options.rational);
+
+        let mut stdin = input.clone();
+        if options.one_ten {
+            stdin = utils::create_str_range(1, 10);
+        }
+        if options.one_hundred {
+            stdin = utils::create_str_range(1, 100);
+        }
+        if options.rangeify {
+            stdin = utils::create_str_range(1, parse_count(&stdin)?);
+        }
+        if options.zero_range {
+            stdin = utils::create_str_range(0, parse_count(&stdin)?.saturating_sub(1));
+        }
+
+        let mut env = Environment::init();
+        env.define_var(
+            "_",
+            // Eval code as input if `-e` present
+            if options.eval {
+                crate::build_ast(&stdin)
+                    .and_then(|ast| {
+                        parse_node(
+                            Rc::new(RefCell::new(env.clone())),
+                            ast.first().unwrap_or(&DEFAULT),
+                        )
+                    })
+                    .map_err(ArnError::without_span)?
+            } else {
+                Dynamic::from(stdin.clone())
+            },
+        );
+        parser::define_constants(&mut env)?;
+
+        if options.input_left || options.input_right {
+            let as_vec = env
+                .get_var("_")?
+                .literal_array()
+                .set_env_self(Rc::new(RefCell::new(env.clone())))
+                .collect::<ArnResult<Vec<_>>>()?;
+            if as_vec.is_empty() {
+                return Err(ArnError::Input("cannot unpack an empty input".to_owned()));
+            }
+
+            if options.input_left {
+                env.define_var("_", as_vec[0].clone());
+                env.define_var("a", as_vec[1..].to_vec());
+            } else {
+                env.define_var("_", as_vec[1..].to_vec());
+                env.define_var("a", as_vec[0].clone());
+            }
+        }
+
+        parser::define_builtins(&mut env, &output);
+
+        Ok(Self {
+            options,
+            input,
+            stdin,
+            output,
+            env: Rc::new(RefCell::new(env)),
+        })
+    }
+
+    #[inline]
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    #[inline]
+    pub fn precision(&self) -> Precision {
+        self.options.precision()
+    }
+
+    // Changes the precision used by everything evaluated from now on
+    pub fn set_precision(&mut self, precision: Precision) -> ArnResult<()> {
+        let precision = check_precision(precision)?;
+        self.options.float_precision = precision.float;
+        self.options.output_precision = precision.output;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn rational_mode(&self) -> RationalMode {
+        self.options.rational
+    }
+
+    // Changes whether divisions evaluated from now on stay exact, and how fractions are printed
+    pub fn set_rational_mode(&mut self, mode: RationalMode) {
+        self.options.rational = mode;
+    }
+
+    // Makes this interpreter's settings the ones used by the current thread, until the result is dropped
+    fn install(&self) -> Installed {
+        Installed::new(self.precision(), self.options.rational)
+    }
+
+    #[inline]
+    pub fn env(&self) -> Env {
+        Rc::clone(&self.env)
+    }
+
+    // Throws away everything defined since the interpreter was created
+    // The precision is a setting rather than a definition, so it is kept
+    pub fn reset(&mut self) -> ArnResult<()> {
+        *self = Self::build(
+            self.options.clone(),
+            self.input.clone(),
+            Rc::clone(&self.output),
+        )?;
+        Ok(())
+    }
+
+    // Writes <value> to the output, one line per entry for sequences
+    // Infinite sequences are written as they are generated, until one of their terms fails
+    pub fn print(&self, value: &Dynamic) -> ArnResult<()> {
+        let _installed = self.install();
+        utils::io::write_value(&self.output, value, "\n")
+    }
+
+    // <value> as `print` would write it, with this interpreter's precision
+    pub fn format(&self, value: &Dynamic) -> String {
+        let _installed = self.install();
+        value.to_string()
+    }
+
+    // Runs <program>, yielding the value of its last expression
+    // Spans in the returned error are relative to <program>
+    pub fn eval(&mut self, program: &str) -> ArnResult<Dynamic> {
+        // Number literals are created with the working precision while lexing
+        let _installed = self.install();
+        let len = program.chars().count();
+        let mut program = program.to_owned();
+        // Each wrapper adds a single char before the program
+        let mut offset = 0;
+        if self.options.array {
+            program = format!("[{program}]");
+            offset += 1;
+        }
+        if self.options.map {
+            program = format!("{{{program}}}\\");
+            offset += 1;
+        }
+        if self.options.flat {
+            program = format!("({program}):_");
+            offset += 1;
+        }
+        if self.options.find {
+            program = format!("({program}):i");
+            offset += 1;
+        }
+
+        crate::build_ast(&program)
+            .and_then(|ast| self.run(&ast))
+            .map_err(|e| e.shift_span(offset, len))
+    }
+
+    pub fn run(&mut self, ast: &[Node]) -> ArnResult<Dynamic> {
+        let _installed = self.install();
+        let env = &self.env;
+
+        for node in ast.iter().take(ast.len().saturating_sub(1)) {
+            parse_node(Rc::clone(env), node)?;
+        }
+
+        let mut result = parse_node(Rc::clone(env), ast.last().unwrap_or(&DEFAULT))?;
+
+        if self.options.first {
+            result = result
+                .literal_array()
+                .set_env_self(Rc::clone(env))
+                .next()
+                .ok_or_else(|| ArnError::empty("-f"))??;
+        }
+        if self.options.last {
+            result = result
+                .literal_array()
+                .set_env_self(Rc::clone(env))
+                .collect::<ArnResult<Vec<_>>>()?
+                .pop()
+                .ok_or_else(|| ArnError::empty("-l"))?;
+        }
+        if self.options.index {
+            let index = parse_count(&self.stdin)?;
+            result = result
+                .literal_array()
+                .set_env_self(Rc::clone(env))
+                .nth(index)
+                .ok_or_else(|| ArnError::IndexOutOfRange(format!("no value at index {index}")))??;
+        }
+        if self.options.sum {
+            let mut sum = Dynamic::from(Int::new());
+            for val in result.literal_array().set_env_self(Rc::clone(env)) {
+                sum = sum.zip_num(&val?, |l, r| Some(l + r), |l, r| l + r);
+            }
+            result = sum;
+        }
+        if self.options.size {
+            let mut size = 0;
+            for val in result.literal_array().set_env_self(Rc::clone(env)) {
+                val?;
+                size += 1;
+            }
+            result = Dynamic::from(Int::from(size));
+        }
+        if self.options.not {
+            result = Dynamic::from(!result.literal_bool());
+        }
+
+        // Evaluate finite results now, so any error is reported instead of cutting the output short
+        if result.is_array() {
+            let mut seq = result.literal_array();
+            seq.force()?;
+            result = Dynamic::from(seq);
+        }
+
+        Ok(result)
+    }
+}
+
+// Reads STDIN as a non-negative integer, for the flags that need one
+fn parse_count(stdin: &str) -> ArnResult<usize> {
+    stdin
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| ArnError::Input(format!("`{stdin}` is not a valid integer")))
+}
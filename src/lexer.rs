@@ -1,10 +1,23 @@
 use crate::utils::consts::OPTIONS;
-use crate::utils::num;
-use crate::utils::tokens::Token;
+use crate::utils::error::ArnResult;
+use crate::utils::num::{self, Cpx};
+use crate::utils::tokens::{Span, Token};
 
 // Takes the inputted program and converts it into a stream of tokens
 // Inserts the implied variable `_` wherever it is used
-pub fn lex(prg: &str) -> Vec<Token> {
+#[inline]
+pub fn lex(prg: &str) -> ArnResult<Vec<Token>> {
+    lex_at(prg, 0)
+}
+
+// Position of the first char in <buf>, which always ends just before the char at <i>
+#[inline]
+fn start(buf: &str, i: usize) -> usize {
+    i - buf.chars().count()
+}
+
+// Lexes <prg>, which begins <offset> chars into the full program
+fn lex_at(prg: &str, offset: usize) -> ArnResult<Vec<Token>> {
     let mut construct: Vec<Token> = Vec::new();
     let mut buf: String = String::new();
 
@@ -16,7 +29,10 @@ pub fn lex(prg: &str) -> Vec<Token> {
     // Mark end of program with `→` (as that character is not supported in decompressed code)
     let bytes = prg.chars().chain("\u{2192}".chars());
 
-    for tok in bytes {
+    for (i, tok) in bytes.enumerate() {
+        // Position of `tok` in the full program
+        let i = i + offset;
+
         if buf == "\"" {
             in_string = true;
             buf.clear();
@@ -34,9 +50,9 @@ pub fn lex(prg: &str) -> Vec<Token> {
         }
 
         // Why `as_ref`? I don't know. It doesn't work when it's `&&buf`
-        if !in_group && ["{", "(", "[", "`", "'"].contains(&buf.as_ref()) {
+        if !in_group && ["{", "(", "[", ".[", "`", "'"].contains(&buf.as_ref()) {
             in_group = true;
-            group_char = Some(buf.chars().next().unwrap());
+            group_char = Some(buf.chars().last().unwrap());
         }
 
         if in_group && group_char.unwrap() == tok {
@@ -53,7 +69,7 @@ pub fn lex(prg: &str) -> Vec<Token> {
             } else if tok == '"' || tok == '→' {
                 construct.push(Token::String(buf.clone()));
                 buf.clear();
-                in_string = false
+                in_string = false;
             } else {
                 buf.push(tok);
             }
@@ -64,7 +80,11 @@ pub fn lex(prg: &str) -> Vec<Token> {
                     group_count -= 1;
                     buf.push(tok);
                 } else {
-                    construct.push(Token::Block(lex(&buf[1..]), '(', None));
+                    construct.push(Token::Block(
+                        lex_at(&buf[1..], start(&buf, i) + 1)?,
+                        '(',
+                        None,
+                    ));
                     buf.clear();
                     in_group = false;
                 }
@@ -77,11 +97,12 @@ pub fn lex(prg: &str) -> Vec<Token> {
                     group_count -= 1;
                     buf.push(tok);
                 } else {
-                    if let Some(Token::Variable(name)) = construct.clone().last() {
+                    let body = lex_at(&buf[1..], start(&buf, i) + 1)?;
+                    if let Some(Token::Variable(name, _)) = construct.clone().last() {
                         construct.pop();
-                        construct.push(Token::Block(lex(&buf[1..]), '{', Some(name.clone())));
+                        construct.push(Token::Block(body, '{', Some(name.clone())));
                     } else {
-                        construct.push(Token::Block(lex(&buf[1..]), '{', None));
+                        construct.push(Token::Block(body, '{', None));
                     }
                     buf.clear();
                     in_group = false;
@@ -90,8 +111,21 @@ pub fn lex(prg: &str) -> Vec<Token> {
                 if group_count > 0 {
                     group_count -= 1;
                     buf.push(tok);
+                } else if buf.starts_with(".[") {
+                    // Map literal, marked with the `.` it starts with
+                    construct.push(Token::Block(
+                        lex_at(&buf[2..], start(&buf, i) + 2)?,
+                        '.',
+                        None,
+                    ));
+                    buf.clear();
+                    in_group = false;
                 } else {
-                    construct.push(Token::Block(lex(&buf[1..]), '[', None));
+                    construct.push(Token::Block(
+                        lex_at(&buf[1..], start(&buf, i) + 1)?,
+                        '[',
+                        None,
+                    ));
                     buf.clear();
                     in_group = false;
                 }
@@ -106,28 +140,50 @@ pub fn lex(prg: &str) -> Vec<Token> {
             } else {
                 buf.push(tok);
             }
+        } else if buf != "_" && tok == 'i' && num::is_arn_num(&buf) {
+            // A number followed by `i` is imaginary
+            let imag = num::parse_arn_num(&buf)
+                .map_err(|e| e.with_span(Span::new(start(&buf, i), i + 1)))?;
+            construct.push(Token::Complex(Box::new(Cpx::with_val(
+                crate::float_precision(),
+                (0, imag),
+            ))));
+            buf.clear();
         } else if buf == "_" || num::is_arn_num(&buf) {
             buf.push(tok);
             if !num::is_arn_num(&buf) {
                 buf.pop();
                 if buf == "_" {
-                    construct.push(Token::Variable("_".to_string()));
+                    construct.push(Token::Variable(
+                        "_".to_string(),
+                        Span::new(start(&buf, i), i),
+                    ));
+                } else if let Some(int) = num::parse_arn_int(&buf) {
+                    construct.push(Token::Integer(int));
                 } else {
                     construct.push(Token::Number(
                         num::parse_arn_num(&buf)
-                            .unwrap_or_else(|_| panic!("Error parsing number `{}`", buf)),
+                            .map_err(|e| e.with_span(Span::new(start(&buf, i), i)))?,
                     ));
                 }
                 buf.clear();
                 buf.push(tok);
             }
-        } else if OPTIONS.operators.iter().any(|i| *i == buf) {
+        } else if buf == "." && tok == '[' {
+            // `.[` starts a map, there is no operator it could be the start of
+            buf.push(tok);
+        } else if OPTIONS.operators.contains(&buf) {
             buf.push(tok);
             let mut consumed = true;
-            if !OPTIONS.operators.iter().any(|i| *i == buf) {
+            if !OPTIONS.operators.contains(&buf) {
                 buf.pop();
                 consumed = false;
             }
+            let span = if consumed {
+                Span::new(start(&buf, i + 1), i + 1)
+            } else {
+                Span::new(start(&buf, i), i)
+            };
 
             // Insert mess of precedence logic here
             let rank = OPTIONS.rank.get(&buf).unwrap();
@@ -143,15 +199,14 @@ pub fn lex(prg: &str) -> Vec<Token> {
                             0
                         }
                     {
-                        construct.push(Token::Variable('_'.to_string()));
+                        construct.push(Token::Variable('_'.to_string(), Span::default()));
                     }
-                } else if let Some(Token::Operator(ident, stack_rank)) = construct
-                    .iter()
-                    .rfind(|m| matches!(m, Token::Operator(_, _)))
+                } else if let Some(Token::Operator(ident, stack_rank, _)) =
+                    construct.iter().rfind(|m| matches!(m, Token::Operator(..)))
                 {
                     let pos = construct
                         .iter()
-                        .rposition(|m| matches!(m, Token::Operator(_, _)))
+                        .rposition(|m| matches!(m, Token::Operator(..)))
                         .unwrap();
                     // The previous op has a lower precedence or no right rank, shouldn't have `_` inserted after it yet
                     let used_rank = if OPTIONS.precedence.get(ident).unwrap()
@@ -164,13 +219,13 @@ pub fn lex(prg: &str) -> Vec<Token> {
                     };
                     if construct.len() - pos <= used_rank {
                         for _ in 0..used_rank - (construct.len() - pos - 1) {
-                            construct.push(Token::Variable('_'.to_string()));
+                            construct.push(Token::Variable('_'.to_string(), Span::default()));
                         }
                     }
                 }
             }
 
-            construct.push(Token::Operator(buf.clone(), *rank));
+            construct.push(Token::Operator(buf.clone(), *rank, span));
 
             buf.clear();
             if !consumed {
@@ -178,7 +233,7 @@ pub fn lex(prg: &str) -> Vec<Token> {
             }
         } else if buf.chars().all(char::is_alphanumeric) && !buf.is_empty() {
             if !tok.is_alphanumeric() {
-                construct.push(Token::Variable(buf.clone()));
+                construct.push(Token::Variable(buf.clone(), Span::new(start(&buf, i), i)));
                 buf.clear();
             }
 
@@ -195,16 +250,43 @@ pub fn lex(prg: &str) -> Vec<Token> {
     // If last op is missing args, push `_`
     let pos = construct
         .iter()
-        .rposition(|n| matches!(n, Token::Operator(_, _)))
+        .rposition(|n| matches!(n, Token::Operator(..)))
         .unwrap_or(0);
-    if let Some(Token::Operator(_, rank)) = construct.get(pos) {
+    if let Some(Token::Operator(_, rank, _)) = construct.get(pos) {
         let given: usize = construct.len() - pos - 1;
         for _ in 0..rank.1 - given as i32 {
-            construct.push(Token::Variable('_'.to_string()));
+            construct.push(Token::Variable('_'.to_string(), Span::default()));
+        }
+    }
+
+    Ok(construct)
+}
+
+// Whether <prg> ends inside of a `{`, `(` or `[` group that hasn't been closed yet
+pub fn is_unclosed(prg: &str) -> bool {
+    let mut depth = 0_usize;
+    let mut quote: Option<char> = None;
+    let mut last = '\u{2192}';
+
+    for tok in prg.chars() {
+        if let Some(q) = quote {
+            if tok == q && (q != '"' || last != '\\') {
+                quote = None;
+            }
+        } else {
+            match tok {
+                '"' | '`' | '\'' => quote = Some(tok),
+                // `.{`, `:{`, `.}` and `:}` are operators
+                '{' | '}' if last == '.' || last == ':' => {}
+                '{' | '(' | '[' => depth += 1,
+                '}' | ')' | ']' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
         }
+        last = tok;
     }
 
-    construct
+    depth > 0
 }
 
 pub fn to_postfix(tokens: &[Token]) -> Vec<Token> {
@@ -227,12 +309,11 @@ fn expr_to_postfix(tokens: &[Token]) -> Vec<Token> {
     let mut output = Vec::with_capacity(tokens.len());
 
     for tok in tokens {
-        if let Token::Operator(right, rank) = tok {
-            while !operators.is_empty() {
-                let op = operators.pop().unwrap();
-                if let Token::Operator(ref left, left_rank) = op {
-                    if OPTIONS.precedence.get(left).is_none()
-                        || OPTIONS.precedence.get(right).is_none()
+        if let Token::Operator(right, rank, _) = tok {
+            while let Some(op) = operators.pop() {
+                if let Token::Operator(ref left, left_rank, _) = op {
+                    if !OPTIONS.precedence.contains_key(left)
+                        || !OPTIONS.precedence.contains_key(right)
                         || (OPTIONS.precedence.get(right).unwrap()
                             > OPTIONS.precedence.get(left).unwrap()
                             && left_rank.1 > 0)
@@ -244,18 +325,20 @@ fn expr_to_postfix(tokens: &[Token]) -> Vec<Token> {
 
                     // If there are missing implied `_` from the program, insert them now. These will always be the last ones (so this won't lead to interpretation issues)
                     // The above will hold true
-                    let total_rank =
-                        crate::utils::sum_rank(left_rank.0 as i128 + left_rank.1 as i128, &output);
+                    let total_rank = crate::utils::sum_rank(
+                        i128::from(left_rank.0) + i128::from(left_rank.1),
+                        &output,
+                    );
                     for _ in 0..(total_rank - output.len() as i128) {
-                        output.push(Token::Variable('_'.to_string()));
+                        output.push(Token::Variable('_'.to_string(), Span::default()));
                     }
-                    output.push(op)
+                    output.push(op);
                 }
             }
 
             operators.push(tok.clone());
         } else if let Token::Block(body, ch, nm) = tok {
-            let new = to_postfix(&body);
+            let new = to_postfix(body);
             output.push(Token::Block(new, *ch, nm.clone()));
         } else {
             output.push(tok.clone());
@@ -263,10 +346,11 @@ fn expr_to_postfix(tokens: &[Token]) -> Vec<Token> {
     }
 
     while !operators.is_empty() {
-        if let Token::Operator(_, rank) = operators.last().unwrap() {
-            let total_rank = crate::utils::sum_rank(rank.0 as i128 + rank.1 as i128, &output);
+        if let Token::Operator(_, rank, _) = operators.last().unwrap() {
+            let total_rank =
+                crate::utils::sum_rank(i128::from(rank.0) + i128::from(rank.1), &output);
             for _ in 0..(total_rank - output.len() as i128) {
-                output.push(Token::Variable('_'.to_string()));
+                output.push(Token::Variable('_'.to_string(), Span::default()));
             }
             output.push(operators.pop().unwrap());
         } else {
@@ -0,0 +1,117 @@
+#![deny(rust_2018_idioms, clippy::all)]
+#![deny(clippy::clone_on_ref_ptr)]
+#![warn(clippy::pedantic)]
+#![allow(
+    clippy::module_name_repetitions,
+    clippy::wildcard_imports,
+    clippy::too_many_lines,
+    // I have a lot of TODOs, so this is reduntant
+    clippy::match_same_arms,
+    // I don't care about this
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    // It's a ******* Rc
+    clippy::needless_pass_by_value,
+    // The public API is small enough that these are just noise
+    clippy::must_use_candidate,
+    clippy::return_self_not_must_use,
+    clippy::missing_panics_doc,
+    clippy::missing_errors_doc,
+    // `Options` mirrors the command line flags
+    clippy::struct_excessive_bools,
+    clippy::non_std_lazy_statics,
+    clippy::used_underscore_items
+)]
+
+#[macro_use]
+extern crate lazy_static;
+
+pub mod ast;
+pub mod interpreter;
+pub mod lexer;
+pub mod parser;
+pub mod utils;
+
+use std::cell::Cell;
+
+pub use interpreter::{Interpreter, Options};
+pub use utils::error::{ArnError, ArnResult};
+pub use utils::{env::Environment, types::Dynamic};
+
+// Precision of internal floats (in bits) and of outputted numbers (in digits)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Precision {
+    pub float: u32,
+    pub output: usize,
+}
+
+impl Default for Precision {
+    fn default() -> Self {
+        Self {
+            float: 50,
+            output: 4,
+        }
+    }
+}
+
+// Whether divisions that don't come out whole stay exact, and how those fractions are printed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RationalMode {
+    /// Fractions become floats
+    #[default]
+    Off,
+    /// Fractions are kept and printed as `n/d`
+    Fraction,
+    /// Fractions are kept, but printed as decimals
+    Decimal,
+}
+
+thread_local! {
+    // Set by an `Interpreter` whenever it runs, so interpreters on other threads don't interfere
+    static PRECISION: Cell<Precision> = Cell::new(Precision::default());
+    static RATIONAL_MODE: Cell<RationalMode> = Cell::new(RationalMode::default());
+}
+
+#[inline]
+pub fn precision() -> Precision {
+    PRECISION.with(Cell::get)
+}
+
+#[inline]
+pub fn float_precision() -> u32 {
+    precision().float
+}
+
+#[inline]
+pub fn output_precision() -> usize {
+    precision().output
+}
+
+pub(crate) fn set_precision(precision: Precision) {
+    PRECISION.with(|cell| cell.set(precision));
+}
+
+#[inline]
+pub fn rational_mode() -> RationalMode {
+    RATIONAL_MODE.with(Cell::get)
+}
+
+pub(crate) fn set_rational_mode(mode: RationalMode) {
+    RATIONAL_MODE.with(|cell| cell.set(mode));
+}
+
+// Runs <f> with a different float precision, restoring the previous one afterwards
+pub(crate) fn with_float_precision<T>(float: u32, f: impl FnOnce() -> T) -> T {
+    let prev = precision();
+    set_precision(Precision { float, ..prev });
+    let result = f();
+    set_precision(prev);
+    result
+}
+
+#[inline]
+pub fn build_ast(prg: &str) -> ArnResult<Vec<utils::tokens::Node>> {
+    Ok(ast::to_ast(&lexer::to_postfix(&lexer::lex(prg)?)))
+}
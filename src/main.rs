@@ -1,38 +1,23 @@
 #![deny(rust_2018_idioms, clippy::all)]
-#![deny(mutable_borrow_reservation_conflict, clippy::clone_on_ref_ptr)]
 #![warn(clippy::pedantic)]
-#![allow(
-    clippy::module_name_repetitions,
-    clippy::wildcard_imports,
-    clippy::too_many_lines,
-    // I have a lot of TODOs, so this is reduntant
-    clippy::match_same_arms,
-    // I don't care about this
-    clippy::cast_possible_wrap,
-    clippy::cast_sign_loss,
-    clippy::cast_possible_truncation,
-    // It's a ******* Rc
-    clippy::needless_pass_by_value
-)]
+#![allow(clippy::non_std_lazy_statics)]
 
 #[macro_use]
 extern crate clap;
 #[macro_use]
 extern crate lazy_static;
 
-#[macro_use]
-mod ast;
-mod lexer;
-mod parser;
-mod utils;
+mod repl;
 
-use std::{fmt::Write as FmtWrite, io::Write as IoWrite};
-use std::{fs, io};
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::io;
 
+use arn_language::utils::io::{Input, StdinInput};
+use arn_language::utils::{compress, dict};
+use arn_language::{build_ast, lexer, Interpreter, Options, RationalMode};
 use clap::{App, Arg, SubCommand};
 
-use crate::utils::compress;
-
 // This is really cursed, but it works so hey
 lazy_static! {
     pub static ref MATCHES: clap::ArgMatches<'static> = App::new("Arn")
@@ -65,6 +50,9 @@ lazy_static! {
                 .help("Precision of internal floats")
                 .takes_value(true)
                 .value_name("INTEGER")
+                .validator(|v| {
+                    whole_number(&v, u64::from(rug::float::prec_min()), u64::from(rug::float::prec_max()))
+                })
         )
         .arg(
             Arg::with_name("output-precision")
@@ -73,6 +61,19 @@ lazy_static! {
                 .help("Precision of outputted numbers")
                 .takes_value(true)
                 .value_name("INTEGER")
+                .validator(|v| whole_number(&v, 0, usize::MAX as u64))
+        )
+        .arg(
+            Arg::with_name("rational")
+                .short("q")
+                .long("rational")
+                .help("Keeps divisions exact, printing fractions as n/d")
+        )
+        .arg(
+            Arg::with_name("rational-decimal")
+                .short("Q")
+                .long("rational-decimal")
+                .help("Keeps divisions exact, printing fractions as decimals")
         )
         .arg(
             Arg::with_name("input")
@@ -95,6 +96,7 @@ lazy_static! {
                 .takes_value(true)
                 .value_name("MEGABYTES")
                 .default_value("2")
+                .validator(|v| whole_number(&v, 1, (usize::MAX >> 20) as u64))
         )
         .arg(
             Arg::with_name("compress")
@@ -102,6 +104,13 @@ lazy_static! {
                 .long("compress")
                 .help("The input will be compressed and printed to STDOUT")
         )
+        .arg(
+            Arg::with_name("compress-string")
+                .long("compress-string")
+                .help("Prints the shortest compressed string literal for the given text")
+                .takes_value(true)
+                .value_name("TEXT")
+        )
         .arg(
             Arg::with_name("debug")
                 .long("debug")
@@ -188,38 +197,24 @@ lazy_static! {
                 .help("Flattens returned value")
         )
         .get_matches();
-    pub static ref FLOAT_PRECISION: u32 = MATCHES
-        .value_of("precision")
-        .unwrap_or("50")
-        .parse()
-        .unwrap();
-    pub static ref OUTPUT_PRECISION: usize = MATCHES
-        .value_of("output-precision")
-        .unwrap_or("4")
-        .parse()
-        .unwrap();
 }
 
 fn main() {
-    if let Some(cli) = MATCHES.subcommand_matches("cli") {
-        // Always present
-        let stdin = cli.value_of("stdin").unwrap();
-        let mut program = String::new();
-        loop {
-            print!("> ");
-            io::stdout().flush().expect("Cannot flush STDOUT");
-            io::stdin()
-                .read_line(&mut program)
-                .expect("Could not read from STDIN");
-            if program == ".exit" {
-                break;
+    if let Some(text) = MATCHES.value_of("compress-string") {
+        match dict::compress(text) {
+            Ok(literal) => println!("{literal}"),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
             }
-            parser::parse(&build_ast(&format!(
-                "_ := ({}),\n{}",
-                stdin,
-                program.trim()
-            )));
         }
+        std::process::exit(0);
+    }
+
+    if let Some(cli) = MATCHES.subcommand_matches("cli") {
+        // Always present
+        repl::run(cli.value_of("stdin").unwrap());
+        std::process::exit(0);
     }
 
     if let Some(path) = MATCHES.value_of("file") {
@@ -227,7 +222,8 @@ fn main() {
         let mut program = read_file(path).replace("\r\n", "\n").trim().to_owned();
 
         if MATCHES.is_present("gen-answer") {
-            let comp_program = compress::pack(&program);
+            // Programs that can't be packed are posted as they are
+            let comp_program = compress::pack(&program).unwrap_or_else(|| program.clone());
             let mut flags = String::new();
             print!("# [Arn](https://github.com/ZippyMagician/Arn)");
             for arg in std::env::args()
@@ -236,12 +232,12 @@ fn main() {
                 .flat_map(|n| n.trim_matches('-').chars().collect::<Vec<_>>())
             {
                 if arg != 'p' && arg != 'o' && arg != 'e' && arg != 'u' {
-                    write!(flags, "{}", arg).unwrap();
+                    write!(flags, "{arg}").unwrap();
                 }
             }
 
             if !flags.is_empty() {
-                print!(" `-{}`", flags);
+                print!(" `-{flags}`");
             }
             println!(
                 ", [{} bytes](https://github.com/ZippyMagician/Arn/wiki/Carn)\n",
@@ -252,10 +248,7 @@ fn main() {
                 crate_version!(),
                 comp_program
             );
-            println!(
-                "# Explained\nUnpacked: `{}`\n```\nELABORATE HERE\n```",
-                program
-            );
+            println!("# Explained\nUnpacked: `{program}`\n```\nELABORATE HERE\n```");
             std::process::exit(0);
         }
 
@@ -266,7 +259,11 @@ fn main() {
             .unwrap();
 
         if MATCHES.is_present("compress") {
-            println!("{}", compress::pack(&program));
+            let Some(packed) = compress::pack(&program) else {
+                eprintln!("Only printable ASCII programs can be compressed");
+                std::process::exit(1);
+            };
+            println!("{packed}");
             std::process::exit(0);
         }
 
@@ -274,19 +271,7 @@ fn main() {
             program = compress::unpack(&program);
         }
 
-        // Some ARGV handling
-        if MATCHES.is_present("array") {
-            program = format!("[{}]", program);
-        }
-        if MATCHES.is_present("map") {
-            program = format!("{{{}}}\\", program);
-        }
-        if MATCHES.is_present("flat") {
-            program = format!("({}):_", program);
-        }
-        if MATCHES.is_present("find") {
-            program = format!("({}):i", program);
-        }
+        let options = options();
 
         // Create thread to run parser in that features much larger stack
         let builder = std::thread::Builder::new()
@@ -299,18 +284,86 @@ fn main() {
                     println!("lexed: {:?}", lexer::lex(&program));
                     println!("ast: {:?}", build_ast(&program));
                 }
-                parser::parse(&build_ast(&program))
+                let run =
+                    Interpreter::with_io(options, input(), io::stdout()).and_then(|mut arn| {
+                        let result = arn.eval(&program)?;
+                        arn.print(&result)
+                    });
+                match run {
+                    Ok(()) => {}
+                    Err(e) => {
+                        eprintln!("{}", e.render(&program));
+                        std::process::exit(1);
+                    }
+                }
             })
             .unwrap();
         handler.join().unwrap();
     }
 }
 
-#[inline]
-pub fn build_ast(prg: &str) -> Vec<utils::tokens::Node> {
-    ast::to_ast(&lexer::to_postfix(&lexer::lex(prg)))
+// Checks that the value of an option is a whole number from <min> to <max>
+fn whole_number(value: &str, min: u64, max: u64) -> Result<(), String> {
+    match value.parse::<u64>() {
+        Ok(n) if (min..=max).contains(&n) => Ok(()),
+        _ => Err(format!(
+            "expected a whole number from {min} to {max}, got `{value}`"
+        )),
+    }
+}
+
+// `-u` if it was passed, otherwise the real STDIN
+fn input() -> Box<dyn Input> {
+    match MATCHES.value_of("input") {
+        Some(val) => Box::new(val.to_owned()),
+        None => Box::new(StdinInput),
+    }
+}
+
+// Collect the interpreter settings from ARGV
+fn options() -> Options {
+    Options {
+        float_precision: MATCHES
+            .value_of("precision")
+            .unwrap_or("50")
+            .parse()
+            .unwrap(),
+        output_precision: MATCHES
+            .value_of("output-precision")
+            .unwrap_or("4")
+            .parse()
+            .unwrap(),
+        rational: if MATCHES.is_present("rational-decimal") {
+            RationalMode::Decimal
+        } else if MATCHES.is_present("rational") {
+            RationalMode::Fraction
+        } else {
+            RationalMode::Off
+        },
+        stdin: String::new(),
+        eval: MATCHES.is_present("eval"),
+        one_ten: MATCHES.is_present("one-ten"),
+        one_hundred: MATCHES.is_present("one-hundred"),
+        rangeify: MATCHES.is_present("rangeify"),
+        zero_range: MATCHES.is_present("0-range"),
+        input_left: MATCHES.is_present("input-left"),
+        input_right: MATCHES.is_present("input-right"),
+        array: MATCHES.is_present("array"),
+        map: MATCHES.is_present("map"),
+        flat: MATCHES.is_present("flat"),
+        find: MATCHES.is_present("find"),
+        first: MATCHES.is_present("first"),
+        last: MATCHES.is_present("last"),
+        index: MATCHES.is_present("index"),
+        sum: MATCHES.is_present("sum"),
+        size: MATCHES.is_present("size"),
+        not: MATCHES.is_present("not"),
+    }
 }
 
 fn read_file(path: &str) -> String {
-    fs::read_to_string(path).unwrap_or_else(|_| panic!("\nFile '{}' does not exist.\n", path))
+    fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("Cannot read the file '{path}': {e}");
+        std::process::exit(1);
+    })
 }
@@ -1,39 +1,61 @@
 use std::cell::RefCell;
-use std::io::{self, Read};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
 use std::rc::Rc;
 
-use radix_fmt::radix;
 use rand::Rng;
+use rug::ops::Pow;
 
-use crate::utils::num::{to_u32, Num};
-use crate::utils::{self, env::Environment, tokens::Node, types::*};
-use crate::{FLOAT_PRECISION, MATCHES};
+use crate::utils::base::{self, Conversion};
+use crate::utils::combinatorics::{self, Arrangement};
+use crate::utils::error::{ArnError, ArnResult};
+use crate::utils::io::{self, Output};
+use crate::utils::num::{keep_exact, to_u32, Cpx, Int, Num, Rat};
+use crate::utils::tokens::{Node, Span};
+use crate::utils::{self, env::Environment, matrix, types::*};
 
 lazy_static! {
     static ref DEFAULT: Node = Node::String(String::new());
     static ref USCORE: String = String::from("_");
 }
 
+// <left> <seperator> <right>, one step of a fold
+fn fold_pair(env: &Env, left: &Dynamic, seperator: &str, right: &Dynamic) -> ArnResult<Dynamic> {
+    let program = format!(
+        "{} {} {}",
+        left.clone().into_node()?,
+        seperator,
+        right.clone().into_node()?
+    );
+    crate::build_ast(&program)
+        .and_then(|ast| parse_node(Rc::clone(env), &ast[0]))
+        .map_err(ArnError::without_span)
+}
+
 fn grab_block_from_fold(fold: &Node, mut block: Option<Node>) -> (Option<Node>, Node) {
     match fold {
-        Node::Op(n, l, r) => {
+        Node::Op(n, l, r, span) => {
             let mut r = r.clone();
             let end = r.len() - 1;
             let inter = grab_block_from_fold(&r[end], block);
 
             block = inter.0;
             r[end] = inter.1;
-            (block, Node::Op(n.clone(), l.clone(), r))
+            (block, Node::Op(n.clone(), l.clone(), r, *span))
         }
 
-        Node::Block(_, _) => (Some(fold.clone()), Node::Variable("_".to_string())),
+        Node::Block(_, _) => (
+            Some(fold.clone()),
+            Node::Variable("_".to_string(), Span::default()),
+        ),
 
         _ => (block, fold.clone()),
     }
 }
 
-pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
-    match op {
+// Evaluates operator <op> with its left and right operands
+pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> ArnResult<Dynamic> {
+    Ok(match op {
         // Assign expression <right> to <left>
         ":=" => {
             let name = format!("{}", left[0]);
@@ -49,70 +71,77 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
         // <right>(<left>)
         "." => {
             let v = format!("{}", right[0]);
-            let arg = parse_node(Rc::clone(&env), &left[0]);
-            env.borrow().attempt_call(v.trim(), &env, arg)
+            let arg = parse_node(Rc::clone(&env), &left[0])?;
+            env.borrow().attempt_call(v.trim(), &env, arg)?
         }
 
         // <left> pow <right>
-        "^" => {
-            let left = parse_node(Rc::clone(&env), &left[0]);
-            if left.is_string() {
-                left.mutate_string(|s| s.repeat(to_u32(&env, &right[0]) as usize))
-            } else {
-                let mut left = left.literal_num();
-                let o = left.clone();
-                let right = to_u32(&env, &right[0]);
-                for _ in 1..right {
-                    left *= o.clone();
-                }
-
-                Dynamic::from(left)
+        "^" => power(&env, &left[0], &right[0])?,
+
+        // <b> pow <e> mod <m>
+        "^%" => {
+            let [base, exp, modulus] = [&right[0], &right[1], &right[2]]
+                .map(|node| parse_node(Rc::clone(&env), node).and_then(to_integer));
+            let (base, exp, modulus) = (base?, exp?, modulus?);
+            if modulus == 0 {
+                return Err(ArnError::Type("`^%` cannot reduce modulo 0".to_owned()));
             }
+            Dynamic::from(base.clone().pow_mod(&exp, &modulus).map_err(|_| {
+                ArnError::Type(format!(
+                    "`{}` has no inverse modulo `{}`",
+                    Dynamic::from(base),
+                    Dynamic::from(modulus)
+                ))
+            })?)
         }
 
         // [<left>, <right>]
         "<>" => {
-            let left = parse_node(Rc::clone(&env), &left[0]);
-            let right = parse_node(Rc::clone(&env), &right[0]);
+            let left = parse_node(Rc::clone(&env), &left[0])?;
+            let right = parse_node(Rc::clone(&env), &right[0])?;
             Dynamic::from([left, right])
         }
 
         // <left> × <right>
-        "*" => {
-            let left = parse_node(Rc::clone(&env), &left[0]);
-            let right = parse_node(Rc::clone(&env), &right[0]).literal_num();
-            left.mutate_num(|n| n * right)
-        }
+        "*" => parse_node(Rc::clone(&env), &left[0])?
+            .arithmetic('*', &parse_node(Rc::clone(&env), &right[0])?),
+
+        // Matrix product, matrix power and dot product
+        ".*" | ".^" | ":." => matrix_op(&env, op, &left[0], &right[0])?,
 
         // <left> ÷ <right>
-        "/" => {
-            let left = parse_node(Rc::clone(&env), &left[0]);
-            let right = parse_node(Rc::clone(&env), &right[0]).literal_num();
-            left.mutate_num(|n| n / right)
-        }
+        "/" => parse_node(Rc::clone(&env), &left[0])?
+            .arithmetic('/', &parse_node(Rc::clone(&env), &right[0])?),
 
         // <left> mod <right>
         "%" => {
-            let left = parse_node(Rc::clone(&env), &left[0]);
-            let right = parse_node(Rc::clone(&env), &right[0]).literal_num();
-            left.mutate_num(|n| n % right)
+            let left = parse_node(Rc::clone(&env), &left[0])?;
+            let right = parse_node(Rc::clone(&env), &right[0])?;
+            left.zip_num(
+                &right,
+                |l, r| {
+                    let quot = (r != 0).then(|| (l.clone() / &r).trunc())?;
+                    keep_exact(l - r * quot)
+                },
+                |l, r| l % r,
+            )
         }
 
         // <left>.join(<right>)
         ":|" => {
-            let mut left = parse_node(Rc::clone(&env), &left[0]).literal_array();
+            let mut left = parse_node(Rc::clone(&env), &left[0])?.literal_array();
             left.set_env(Rc::clone(&env));
             Dynamic::from(
-                left.map(|dy| format!("{}", dy))
-                    .collect::<Vec<String>>()
-                    .join(&parse_node(Rc::clone(&env), &right[0]).literal_string()),
+                left.map(|dy| dy.map(|dy| format!("{dy}")))
+                    .collect::<ArnResult<Vec<String>>>()?
+                    .join(&parse_node(Rc::clone(&env), &right[0])?.literal_string()),
             )
         }
 
         // <left>.split(<right>)
         ":!" => {
-            let left = parse_node(Rc::clone(&env), &left[0]).literal_string();
-            let right = parse_node(Rc::clone(&env), &right[0]).literal_string();
+            let left = parse_node(Rc::clone(&env), &left[0])?.literal_string();
+            let right = parse_node(Rc::clone(&env), &right[0])?.literal_string();
 
             Dynamic::from(
                 left.split(&right)
@@ -122,52 +151,69 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
         }
 
         // <left> + <right>
-        "+" => {
-            let left = parse_node(Rc::clone(&env), &left[0]);
-            let right = parse_node(Rc::clone(&env), &right[0]).literal_num();
-            left.mutate_num(|n| n + right)
-        }
+        "+" => parse_node(Rc::clone(&env), &left[0])?
+            .arithmetic('+', &parse_node(Rc::clone(&env), &right[0])?),
 
         // <left> - <right>
-        "-" => {
-            let left = parse_node(Rc::clone(&env), &left[0]);
-            let right = parse_node(Rc::clone(&env), &right[0]).literal_num();
-            left.mutate_num(|n| n - right)
-        }
+        "-" => parse_node(Rc::clone(&env), &left[0])?
+            .arithmetic('-', &parse_node(Rc::clone(&env), &right[0])?),
 
         // <left> ==> [<left>[..<right>], <left>[<right>..]]
         ".$" => {
-            let mut left = parse_node(Rc::clone(&env), &left[0]).literal_array();
-            let i = to_u32(&env, &right[0]) as usize;
+            let mut left = parse_node(Rc::clone(&env), &left[0])?.literal_array();
+            let i = to_u32(&env, &right[0])? as usize;
             left.set_env(Rc::clone(&env));
-            let seq = left.collect::<Vec<Dynamic>>();
+            let seq = left.collect::<ArnResult<Vec<Dynamic>>>()?;
+            if i > seq.len() {
+                return Err(ArnError::IndexOutOfRange(format!(
+                    "cannot split a sequence of length {} at {i}",
+                    seq.len()
+                )));
+            }
 
             let (l, r) = seq.split_at(i);
             Dynamic::from([l, r])
         }
 
+        // <left> with the key and value in <right> inserted
+        ".+" => {
+            let mut map = parse_node(Rc::clone(&env), &left[0])?.literal_map()?;
+            let mut pair = parse_node(Rc::clone(&env), &right[0])?
+                .literal_array()
+                .set_env_self(Rc::clone(&env));
+            match (pair.next(), pair.next()) {
+                (Some(key), Some(value)) => map.insert(key?, value?),
+                _ => {
+                    return Err(ArnError::Type(
+                        "`.+` needs a key and a value to insert".to_owned(),
+                    ))
+                }
+            }
+            Dynamic::from(map)
+        }
+
         // Descending range [<left>, 1]
         ".~" => {
-            let end = to_u32(&env, &left[0]) as usize;
+            let end = to_u32(&env, &left[0])? as usize;
 
             Dynamic::from(
                 (1..=end)
                     .rev()
-                    .map(|n| Dynamic::from(Num::with_val(*FLOAT_PRECISION, n)))
+                    .map(|n| Dynamic::from(Int::from(n)))
                     .collect::<Vec<_>>(),
             )
         }
 
         // [<left>, <right>]
         "=>" => {
-            let left = to_u32(&env, &left[0]) as usize;
-            let right = to_u32(&env, &right[0]) as usize;
+            let left = to_u32(&env, &left[0])? as usize;
+            let right = to_u32(&env, &right[0])? as usize;
 
             Dynamic::new(
                 Val::Array(Box::new(Sequence::from_iter(
-                    (left..=right).map(|n| Dynamic::from(Num::with_val(*FLOAT_PRECISION, n))),
+                    (left..=right).map(|n| Dynamic::from(Int::from(n))),
                     Node::Block(vec![], None),
-                    Some(right - left + 1),
+                    Some((right + 1).saturating_sub(left)),
                 ))),
                 4,
             )
@@ -175,14 +221,14 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
 
         // [<left>, <right>)
         "->" => {
-            let left = to_u32(&env, &left[0]) as usize;
-            let right = to_u32(&env, &right[0]) as usize;
+            let left = to_u32(&env, &left[0])? as usize;
+            let right = to_u32(&env, &right[0])? as usize;
 
             Dynamic::new(
                 Val::Array(Box::new(Sequence::from_iter(
-                    (left..right).map(|n| Dynamic::from(Num::with_val(*FLOAT_PRECISION, n))),
+                    (left..right).map(|n| Dynamic::from(Int::from(n))),
                     Node::Block(vec![], None),
-                    Some(right - left),
+                    Some(right.saturating_sub(left)),
                 ))),
                 4,
             )
@@ -190,11 +236,11 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
 
         // [1, <right>]
         "~" => {
-            let right = to_u32(&env, &right[0]) as usize;
+            let right = to_u32(&env, &right[0])? as usize;
 
             Dynamic::new(
                 Val::Array(Box::new(Sequence::from_iter(
-                    (1..=right).map(|n| Dynamic::from(Num::with_val(*FLOAT_PRECISION, n))),
+                    (1..=right).map(|n| Dynamic::from(Int::from(n))),
                     Node::Block(vec![], None),
                     Some(right),
                 ))),
@@ -203,118 +249,37 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
         }
 
         // <left>.length
-        "#" => Dynamic::from(Num::with_val(
-            *FLOAT_PRECISION,
-            parse_node(Rc::clone(&env), &left[0])
-                .literal_array()
-                .len()
-                .expect("Cannot take length of infinite sequence"),
-        )),
+        "#" => Dynamic::from(Int::from(sequence_len(
+            &parse_node(Rc::clone(&env), &left[0])?.literal_array(),
+            "#",
+        )?)),
 
         // Base conversion of <left> based on <right>
-        ";" => {
-            let ops = if env.borrow().vals.get(&right[0].to_string()).is_none() {
-                right[0].to_string()
-            } else {
-                parse_node(Rc::clone(&env), &right[0]).to_string()
-            };
-            let chars = ops.trim().trim_matches('"').chars();
-            let mut cur = parse_node(Rc::clone(&env), &left[0]);
-            cur = if cur.clone().to_string().matches('\n').count() > 0 {
-                let temp = cur
-                    .to_string()
-                    .trim()
-                    .split('\n')
-                    .map(str::to_owned)
-                    .collect::<Vec<_>>();
-                if chars
-                    .clone()
-                    .next()
-                    .map_or(true, |c| c != 'H' && c != 'O' && c != 'B')
-                {
-                    Dynamic::from(temp)
-                } else {
-                    Dynamic::from(temp.join(""))
-                }
-            } else {
-                cur.into_string()
-            };
-            let mut num = String::new();
-
-            for char in chars.chain("\u{2192}".chars()) {
-                if !num.is_empty() && !char.is_numeric() {
-                    cur = Dynamic::from(utils::nbase_padded(cur, |cur| {
-                        radix(
-                            cur.parse::<i128>().expect("Invalid base10 number"),
-                            num.parse().unwrap(),
-                        )
-                        .to_string()
-                    }));
-                    num.clear();
-                }
-
-                cur = match char {
-                    'b' => Dynamic::from(utils::nbase_padded(cur, |cur| {
-                        radix(cur.parse::<i128>().expect("Invalid base10 number"), 2).to_string()
-                    })),
-                    'o' => Dynamic::from(utils::nbase_padded(cur, |cur| {
-                        radix(cur.parse::<i128>().expect("Invalid base10 number"), 8).to_string()
-                    })),
-                    'h' => Dynamic::from(utils::nbase_padded(cur, |cur| {
-                        radix(cur.parse::<i128>().expect("Invalid base10 number"), 16).to_string()
-                    })),
-                    'B' => Dynamic::from(
-                        i128::from_str_radix(&cur.literal_string(), 2)
-                            .expect("Invalid base2 number")
-                            .to_string(),
-                    ),
-                    'O' => Dynamic::from(
-                        i128::from_str_radix(&cur.literal_string(), 8)
-                            .expect("Invalid base8 number")
-                            .to_string(),
-                    ),
-                    'H' => Dynamic::from(
-                        i128::from_str_radix(&cur.literal_string(), 16)
-                            .expect("Invalid base16 number")
-                            .to_string(),
-                    ),
-                    '0'..='9' => {
-                        num.push(char);
-                        cur
-                    }
-                    '\u{2192}' => cur,
-                    _ => panic!("Unrecognized base conversion char {}", char),
-                }
-            }
-
-            Dynamic::from(cur)
-        }
+        ";" => convert_base(&env, &left[0], &right[0])?,
 
         // Flatten <left>
         ":_" => {
-            let orig = parse_node(Rc::clone(&env), &left[0]).literal_array();
+            let orig = parse_node(Rc::clone(&env), &left[0])?.literal_array();
             if !orig.is_finite() {
-                panic!("Cannot flatten infinite sequence");
+                return Err(ArnError::infinite(":_"));
             }
 
             // Get a ballpark for allocation size
             let mut new = Vec::with_capacity(
-                orig.len().unwrap()
-                    * orig
-                        .clone()
-                        .next()
-                        .unwrap()
-                        .literal_array()
-                        .len()
-                        .expect("Cannot flatten sequence of inifnite sequences"),
+                orig.len().unwrap_or(0)
+                    * match orig.clone().next() {
+                        Some(first) => first?.literal_array().len().unwrap_or(0),
+                        None => 0,
+                    },
             );
             for dy in orig {
+                let dy = dy?;
                 if dy.is_array() {
-                    for n in parse_node(Rc::clone(&env), &dy.into_node()).literal_array() {
-                        new.push(n);
+                    for n in parse_node(Rc::clone(&env), &dy.into_node()?)?.literal_array() {
+                        new.push(n?);
                     }
                 } else {
-                    new.push(dy.clone());
+                    new.push(dy);
                 }
             }
 
@@ -323,12 +288,12 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
 
         // Transpose <left>
         ":%" => {
-            let mut parent = parse_node(Rc::clone(&env), &left[0]).literal_array();
+            let mut parent = parse_node(Rc::clone(&env), &left[0])?.literal_array();
             parent.set_env(Rc::clone(&env));
 
             let mut pre = Vec::new();
             for item in parent {
-                pre.push(item.literal_array());
+                pre.push(item?.literal_array());
             }
 
             Dynamic::from(
@@ -351,35 +316,44 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
                         .filter_map(|array| array.clone().nth(i))
                         .collect()
                 })
-                .collect::<Vec<Vec<Dynamic>>>(),
+                .collect::<ArnResult<Vec<Vec<Dynamic>>>>()?,
             )
         }
 
         // |<left>|
         ".|" => {
-            let left = parse_node(Rc::clone(&env), &left[0]);
-            left.mutate_num(Num::abs)
+            let left = parse_node(Rc::clone(&env), &left[0])?;
+            left.mutate_complex(Cpx::abs)
+                .unwrap_or_else(|| left.mutate_exact(|n| Some(n.abs()), Num::abs))
         }
 
+        // Real part, imaginary part, conjugate and argument of <left>
+        ":R" | ":I" | ":C" | ":A" => complex_part(op, parse_node(Rc::clone(&env), &left[0])?),
+
         // Reverse <left>
         ".<" => Dynamic::from(
-            parse_node(Rc::clone(&env), &left[0])
+            parse_node(Rc::clone(&env), &left[0])?
                 .literal_array()
                 .rev()
-                .collect::<Vec<Dynamic>>(),
+                .collect::<ArnResult<Vec<Dynamic>>>()?,
         ),
 
         // Rangify <left>, exclusive or inclusive
         ".." | ".=" => {
-            let mut left = parse_node(Rc::clone(&env), &left[0]).literal_array();
+            let mut left = parse_node(Rc::clone(&env), &left[0])?.literal_array();
             left.set_env(Rc::clone(&env));
             let left: Vec<u32> = left
                 .map(|n| {
-                    n.literal_num()
-                        .to_u32_saturating_round(rug::float::Round::Down)
-                        .unwrap()
+                    let n = n?.literal_num();
+                    n.to_u32_saturating_round(rug::float::Round::Down)
+                        .ok_or_else(|| ArnError::Type(format!("`{n}` is not a valid range bound")))
                 })
-                .collect();
+                .collect::<ArnResult<_>>()?;
+            if left.len() < 2 {
+                return Err(ArnError::IndexOutOfRange(format!(
+                    "`{op}` needs both a start and an end"
+                )));
+            }
 
             #[allow(clippy::range_plus_one)]
             let range = if op == ".." {
@@ -390,9 +364,7 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
 
             Dynamic::new(
                 Val::Array(Box::new(Sequence::from_iter(
-                    range
-                        .clone()
-                        .map(|n| Dynamic::from(Num::with_val(*FLOAT_PRECISION, n))),
+                    range.clone().map(|n| Dynamic::from(Int::from(n))),
                     Node::Block(vec![], None),
                     Some(range.count()),
                 ))),
@@ -402,7 +374,7 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
 
         // Split <left> on newlines
         ":n" => Dynamic::from(
-            parse_node(Rc::clone(&env), &left[0])
+            parse_node(Rc::clone(&env), &left[0])?
                 .literal_string()
                 .split('\n')
                 .map(str::to_owned)
@@ -411,7 +383,7 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
 
         // Split <left> on spaces
         ":s" => Dynamic::from(
-            parse_node(Rc::clone(&env), &left[0])
+            parse_node(Rc::clone(&env), &left[0])?
                 .literal_string()
                 .split(' ')
                 .map(str::to_owned)
@@ -420,85 +392,111 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
 
         // Tail of <left>
         ":}" => {
-            let mut seq = parse_node(Rc::clone(&env), &left[0]).literal_array();
+            let mut seq = parse_node(Rc::clone(&env), &left[0])?.literal_array();
             seq.set_env(Rc::clone(&env));
 
-            seq.last()
-                .expect("Cannot take last element of infinite sequence")
+            if !seq.is_finite() {
+                return Err(ArnError::infinite(":}"));
+            }
+
+            seq.collect::<ArnResult<Vec<Dynamic>>>()?
+                .pop()
+                .ok_or_else(|| ArnError::empty(":}"))?
         }
 
         // Head of <left>
         ":{" => {
-            let mut seq = parse_node(Rc::clone(&env), &left[0]).literal_array();
-            seq.set_env(Rc::clone(&env));
-
-            seq.next().unwrap()
-        }
-
-        // Behead <left>
-        ".{" => {
-            let mut seq = parse_node(Rc::clone(&env), &left[0]).literal_array();
+            let mut seq = parse_node(Rc::clone(&env), &left[0])?.literal_array();
             seq.set_env(Rc::clone(&env));
 
-            Dynamic::from(seq.collect::<Vec<Dynamic>>()[1..].to_owned())
+            seq.next().ok_or_else(|| ArnError::empty(":{"))??
         }
 
-        // Drop <left>
-        ".}" => {
-            let mut seq = parse_node(Rc::clone(&env), &left[0]).literal_array();
-            seq.set_env(Rc::clone(&env));
+        // Behead <left>, drop the last value of <left>
+        ".{" | ".}" => {
+            let seq = parse_node(Rc::clone(&env), &left[0])?
+                .literal_array()
+                .set_env_self(Rc::clone(&env));
+            if seq.clone().next().is_none() {
+                return Err(ArnError::empty(op));
+            }
 
-            Dynamic::from(seq.clone().collect::<Vec<Dynamic>>()[..seq.count() - 1].to_owned())
+            let (start, end) = if op == ".{" {
+                (Some(1), None)
+            } else {
+                (None, Some(-1))
+            };
+            slice(&env, Dynamic::from(seq), op, start, end, 1)?
         }
 
         // Group entries in <left> based on frequencies
         ":@" => {
-            let mut arr = parse_node(Rc::clone(&env), &left[0]).literal_array();
-            arr.set_env(Rc::clone(&env));
-            let arr = arr.collect::<Vec<Dynamic>>();
+            let arr = parse_node(Rc::clone(&env), &left[0])?
+                .literal_array()
+                .set_env_self(Rc::clone(&env));
+            Dynamic::from(
+                group_by(arr, ":@", |val| Ok(val.clone()))?
+                    .into_iter()
+                    .map(|(_, group)| group)
+                    .collect::<Vec<_>>(),
+            )
+        }
 
-            Dynamic::from(arr.iter().fold(Vec::new(), |mut acc, val| {
-                if acc.is_empty() {
-                    vec![vec![val.clone()]]
-                } else {
-                    let filter = acc.iter().filter(|e| e[0].clone() == val.clone());
-                    if filter.clone().count() > 0 {
-                        let filter = filter.cloned().collect::<Vec<_>>();
-                        let pos = acc.iter().cloned().position(|e| e == filter[0]).unwrap();
-                        acc[pos].push(val.clone());
-                        acc
-                    } else {
-                        acc.push(vec![val.clone()]);
-                        acc
-                    }
-                }
-            }))
+        // Map of each value in <left> to the number of times it appears
+        ":c" => {
+            let arr = parse_node(Rc::clone(&env), &left[0])?
+                .literal_array()
+                .set_env_self(Rc::clone(&env));
+            Dynamic::from(
+                group_by(arr, ":c", |val| Ok(val.clone()))?
+                    .into_iter()
+                    .map(|(key, group)| (key, Dynamic::from(Int::from(group.len()))))
+                    .collect::<Map>(),
+            )
         }
 
+        // Keys of <left>
+        ":k" => Dynamic::from(
+            parse_node(Rc::clone(&env), &left[0])?
+                .literal_map()?
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>(),
+        ),
+
+        // Values of <left>
+        ":V" => Dynamic::from(
+            parse_node(Rc::clone(&env), &left[0])?
+                .literal_map()?
+                .values()
+                .cloned()
+                .collect::<Vec<_>>(),
+        ),
+
         // is <left> perfect square?
         "^*" => {
-            let left = parse_node(Rc::clone(&env), &left[0]).literal_num();
-            Dynamic::from(left.sqrt().is_integer())
+            let left = parse_node(Rc::clone(&env), &left[0])?;
+            Dynamic::from(match left.clone().literal_int() {
+                Some(n) => n.is_perfect_square(),
+                None => left.literal_num().sqrt().is_integer(),
+            })
         }
 
         // Repeat <r1> <r3> times with initial value <r2>
         "&." => {
-            let mut loop_arg = parse_node(Rc::clone(&env), &right[1]);
-            let count = parse_node(Rc::clone(&env), &right[2])
-                .literal_num()
-                .to_u32_saturating_round(rug::float::Round::Down)
-                .unwrap();
+            let mut loop_arg = parse_node(Rc::clone(&env), &right[1])?;
+            let count = to_u32(&env, &right[2])?;
             let child_env = Rc::new(env.as_ref().clone());
 
             for _ in 0..count {
                 if let Node::Block(_, name) = &right[0] {
                     child_env
                         .borrow_mut()
-                        .define_var(name.as_ref().unwrap_or(&USCORE), loop_arg)
+                        .define_var(name.as_ref().unwrap_or(&USCORE), loop_arg);
                 } else {
                     child_env.borrow_mut().define_var("_", loop_arg);
                 }
-                loop_arg = parse_node_uniq(Rc::clone(&child_env), &right[0]);
+                loop_arg = parse_node_uniq(Rc::clone(&child_env), &right[0])?;
             }
 
             loop_arg
@@ -506,52 +504,82 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
 
         // Index of <right> in <left>
         ":i" => {
-            let mut seq = parse_node(Rc::clone(&env), &left[0]).literal_array();
-            let val = parse_node(Rc::clone(&env), &right[0]);
+            let mut seq = parse_node(Rc::clone(&env), &left[0])?.literal_array();
+            let val = parse_node(Rc::clone(&env), &right[0])?;
             seq.set_env(Rc::clone(&env));
 
-            Dynamic::from(Num::with_val(
-                *FLOAT_PRECISION,
-                seq.position(|e| e == val).map_or(-1, |v| v as i128),
-            ))
+            let mut pos = -1;
+            for (i, e) in seq.enumerate() {
+                if e? == val {
+                    pos = i as i128;
+                    break;
+                }
+            }
+
+            Dynamic::from(Int::from(pos))
         }
 
+        // Evaluate <right> with <left> bits of float precision
+        ":p" => {
+            let bits = to_u32(&env, &left[0])?;
+            if bits < rug::float::prec_min() || bits > rug::float::prec_max() {
+                return Err(ArnError::Type(format!("`{bits}` is not a valid precision")));
+            }
+
+            crate::with_float_precision(bits, || parse_node_uniq(Rc::clone(&env), &right[0]))?
+        }
+
+        // <right>-permutations and <right>-combinations of <left>, or how many there are of a number
+        ":P" | ":B" => pick(&env, op, &left[0], &right[0])?,
+
         // not <right>
-        "!" => Dynamic::from(!parse_node(Rc::clone(&env), &right[0]).literal_bool()),
+        "!" => Dynamic::from(!parse_node(Rc::clone(&env), &right[0])?.literal_bool()),
 
         // Filter <r2> with condition <r1>, <r2>.any(<r1>)
         "$" | "$:" => {
-            let mut seq = parse_node(Rc::clone(&env), &right[1]).literal_array();
+            let mut seq = parse_node(Rc::clone(&env), &right[1])?.literal_array();
             seq.set_env(Rc::clone(&env));
             let child_env = Rc::new(env.as_ref().clone());
-            let filter = seq
-                .filter(|v| {
-                    if let Node::Block(_, name) = &right[0] {
-                        child_env
-                            .borrow_mut()
-                            .define_var(name.as_ref().unwrap_or(&USCORE), v.clone())
-                    } else {
-                        child_env.borrow_mut().define_var("_", v.clone());
-                    }
+            if op == "$" && !seq.is_finite() {
+                return Ok(Dynamic::from(Sequence::lazy(Lazy::Filter(
+                    Box::new(seq),
+                    right[0].clone(),
+                    child_env,
+                ))));
+            }
 
-                    parse_node_uniq(Rc::clone(&child_env), &right[0]).literal_bool()
-                })
-                .collect::<Vec<_>>();
+            let mut filter = Vec::new();
+            for v in seq {
+                let v = v?;
+                if call(&child_env, &right[0], v.clone())?.literal_bool() {
+                    // `$:` is settled by the first match, even on an infinite sequence
+                    if op == "$:" {
+                        return Ok(Dynamic::from(true));
+                    }
+                    filter.push(v);
+                }
+            }
 
             if op == "$" {
                 Dynamic::from(filter)
             } else {
-                Dynamic::from(!filter.is_empty())
+                Dynamic::from(false)
             }
         }
 
+        // Values of <r2> up to the first that isn't truthy when bound to <r1>
+        ":$" => take_while(&env, &right[0], &right[1])?,
+
         // Fold + map op, Cumulative fold + map op
         "\\" | ":\\" => {
             let seq = Box::new(
-                parse_node(Rc::clone(&env), &right[0])
+                parse_node(Rc::clone(&env), &right[0])?
                     .literal_array()
                     .set_env_self(Rc::clone(&env)),
             );
+            if !seq.is_finite() {
+                return Err(ArnError::infinite(op));
+            }
 
             let (block, rest) = grab_block_from_fold(&left[0], None);
 
@@ -562,140 +590,142 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
                 seq.map(|val| {
                     child_env
                         .borrow_mut()
-                        .define_var(name.as_ref().unwrap_or(&USCORE), val);
+                        .define_var(name.as_ref().unwrap_or(&USCORE), val?);
                     parse_node_uniq(Rc::clone(&child_env), &block)
                 })
-                .collect::<Vec<_>>()
+                .collect::<ArnResult<Vec<_>>>()?
             } else {
-                seq.collect::<Vec<_>>()
+                seq.collect::<ArnResult<Vec<_>>>()?
             });
 
-            if rest == Node::Variable("_".to_string()) {
+            if rest == Node::Variable("_".to_string(), Span::default()) {
                 Dynamic::from(res.as_ref().clone())
-            } else if op == "\\" {
-                let constructed = format!("{}", rest);
-                let seperator = constructed.trim().trim_matches('_');
-                let program = res
-                    .iter()
-                    .cloned()
-                    .map(|n| format!("{}", n.into_node()))
-                    .collect::<Vec<_>>()
-                    .join(seperator);
-
-                // This looks like a Vec<Node>, but in reality it is a single Node (only one value)
-                let val = crate::build_ast(&program);
-                parse_node(Rc::clone(&env), &val[0])
             } else {
-                let constructed = format!("{}", rest);
+                let constructed = format!("{rest}");
                 let seperator = constructed.trim().trim_matches('_');
+                // One pair at a time from the left, so long sequences don't nest deeply
                 let mut v = res.as_ref().clone();
                 for i in 1..v.len() {
-                    v[i] = parse_node(
-                        Rc::clone(&env),
-                        &crate::build_ast(&format!(
-                            "{} {} {}",
-                            v[i - 1].clone().into_node(),
-                            seperator,
-                            v[i].clone().into_node()
-                        ))[0],
-                    )
+                    v[i] = fold_pair(&env, &v[i - 1], seperator, &v[i])?;
                 }
 
-                Dynamic::from(v)
+                match v.pop() {
+                    Some(last) if op == "\\" => last,
+                    None if op == "\\" => parse_node(Rc::clone(&env), &DEFAULT)?,
+                    last => {
+                        v.extend(last);
+                        Dynamic::from(v)
+                    }
+                }
             }
         }
 
         // Floor <right>
         ":v" => {
-            let right = parse_node(Rc::clone(&env), &right[0]);
+            let right = parse_node(Rc::clone(&env), &right[0])?;
             if right.is_string() {
-                right.mutate_string(|s| s.to_ascii_lowercase())
+                right.mutate_string(|s| s.to_lowercase())
             } else {
-                right.mutate_num(Num::floor)
+                right.mutate_num(Num::floor).into_exact()
             }
         }
 
         // Ceil <right>
         ":^" => {
-            let right = parse_node(Rc::clone(&env), &right[0]);
+            let right = parse_node(Rc::clone(&env), &right[0])?;
             if right.is_string() {
-                right.mutate_string(|s| s.to_ascii_uppercase())
+                right.mutate_string(|s| s.to_uppercase())
             } else {
-                right.mutate_num(Num::ceil)
+                right.mutate_num(Num::ceil).into_exact()
             }
         }
 
         // Inc <right>
         "++" => {
-            if let Node::Variable(name) = &right[0] {
-                let mut val = env.borrow().get_var(name);
-                val = val.mutate_num(|n| n + 1);
+            if let Node::Variable(name, _) = &right[0] {
+                let mut val = env.borrow().get_var(name)?;
+                val = val.mutate_exact(|n| Some(n + 1), |n| n + 1);
                 env.borrow_mut().define_var(name, val.clone());
                 val
             } else {
-                let right = parse_node(Rc::clone(&env), &right[0]);
-                right.mutate_num(|n| n + 1)
+                let right = parse_node(Rc::clone(&env), &right[0])?;
+                right.mutate_exact(|n| Some(n + 1), |n| n + 1)
             }
         }
 
         // Dec <right>
         "--" => {
-            if let Node::Variable(name) = &right[0] {
-                let mut val = env.borrow().get_var(name);
-                val = val.mutate_num(|n| n - 1);
+            if let Node::Variable(name, _) = &right[0] {
+                let mut val = env.borrow().get_var(name)?;
+                val = val.mutate_exact(|n| Some(n - 1), |n| n - 1);
                 env.borrow_mut().define_var(name, val.clone());
                 val
             } else {
-                let right = parse_node(Rc::clone(&env), &right[0]);
-                right.mutate_num(|n| n - 1)
+                let right = parse_node(Rc::clone(&env), &right[0])?;
+                right.mutate_exact(|n| Some(n - 1), |n| n - 1)
             }
         }
 
         // <right> ^ 2
-        ":*" => parse_node(Rc::clone(&env), &right[0]).mutate_num(Num::square),
+        ":*" => unary_arithmetic(op, parse_node(Rc::clone(&env), &right[0])?),
 
-        // √<right>
-        ":/" => parse_node(Rc::clone(&env), &right[0]).mutate_num(Num::sqrt),
+        // √<right>, complex when <right> is negative
+        ":/" => unary_arithmetic(op, parse_node(Rc::clone(&env), &right[0])?),
 
         // 2<right>
-        ":+" => parse_node(Rc::clone(&env), &right[0]).mutate_num(|n| n * 2),
+        ":+" => unary_arithmetic(op, parse_node(Rc::clone(&env), &right[0])?),
 
         // ½<right>
-        ":-" => parse_node(Rc::clone(&env), &right[0]).mutate_num(|n| n / 2),
+        ":-" => unary_arithmetic(op, parse_node(Rc::clone(&env), &right[0])?),
 
         // Sort <right> in descending order
         ":>" => {
-            let mut seq = parse_node(Rc::clone(&env), &right[0]).literal_array();
+            let mut seq = parse_node(Rc::clone(&env), &right[0])?.literal_array();
             seq.set_env(Rc::clone(&env));
-            let mut seq = seq.collect::<Vec<_>>();
-            seq.sort_by(|a, b| b.partial_cmp(a).unwrap());
+            if !seq.is_finite() {
+                return Err(ArnError::infinite(":>"));
+            }
+            let mut seq = seq.collect::<ArnResult<Vec<_>>>()?;
+            seq.sort_by(|a, b| b.cmp(a));
             Dynamic::from(seq)
         }
 
         // Sort <right> in ascending order
         ":<" => {
-            let mut seq = parse_node(Rc::clone(&env), &right[0]).literal_array();
+            let mut seq = parse_node(Rc::clone(&env), &right[0])?.literal_array();
             seq.set_env(Rc::clone(&env));
-            let mut seq = seq.collect::<Vec<_>>();
-            seq.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            if !seq.is_finite() {
+                return Err(ArnError::infinite(":<"));
+            }
+            let mut seq = seq.collect::<ArnResult<Vec<_>>>()?;
+            seq.sort();
             Dynamic::from(seq)
         }
 
         // Bifurcate <right>
         "|:" => {
-            let string = parse_node(Rc::clone(&env), &right[0]).literal_string();
-            let mid = string.len() / 2;
+            let chars = parse_node(Rc::clone(&env), &right[0])?
+                .literal_string()
+                .chars()
+                .collect::<Vec<_>>();
+            let (left, right) = chars.split_at(chars.len() / 2);
             Dynamic::from([
-                string[..mid].to_owned(),
-                string[mid..].to_owned().chars().rev().collect(),
+                left.iter().collect::<String>(),
+                right.iter().rev().collect(),
             ])
         }
 
         // Get random item within <right>
         "?." => {
-            let mut seq = parse_node(Rc::clone(&env), &right[0]).literal_array();
+            let mut seq = parse_node(Rc::clone(&env), &right[0])?.literal_array();
             seq.set_env(Rc::clone(&env));
-            let seq = seq.collect::<Vec<_>>();
+            if !seq.is_finite() {
+                return Err(ArnError::infinite("?."));
+            }
+            let seq = seq.collect::<ArnResult<Vec<_>>>()?;
+            if seq.is_empty() {
+                return Err(ArnError::empty("?."));
+            }
 
             let mut rng = rand::thread_rng();
 
@@ -704,53 +734,46 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
 
         // All primes up to <right>
         "#." => {
-            let num = to_u32(&env, &right[0]) as usize;
-            let sieve = primal::Primes::all()
-                .take_while(|n| *n <= num)
-                .map(|n| Num::with_val(*FLOAT_PRECISION, n))
-                .collect::<Vec<_>>();
-            Dynamic::new(
-                Val::Array(Box::new(Sequence::from_vec(
-                    &sieve,
-                    Node::Block(vec![], None),
-                    Some(sieve.len()),
-                ))),
-                4,
+            let bound = to_integer(parse_node(Rc::clone(&env), &right[0])?)?;
+            let bound = if bound < 2 {
+                0
+            } else {
+                bound.to_usize().ok_or_else(|| {
+                    ArnError::Type(format!(
+                        "`{}` is too large to list the primes up to",
+                        Dynamic::from(bound)
+                    ))
+                })?
+            };
+            Dynamic::from(
+                primal::Primes::all()
+                    .take_while(|n| *n <= bound)
+                    .map(Int::from)
+                    .collect::<Vec<_>>(),
             )
         }
 
-        // All factors of <right>
+        // All factors of <right>, with <right> first and 1 last
         "*." => {
-            let num = to_u32(&env, &right[0]) as usize;
-            let mut fac = Vec::new();
-            let mut i = 1;
-            let mut ind = 0;
-
-            while i <= (num as f64).sqrt().floor() as usize {
-                if num % i == 0 {
-                    fac.insert(ind, Num::with_val(*FLOAT_PRECISION, i));
-                    if i != num / i {
-                        fac.insert(fac.len() - ind, Num::with_val(*FLOAT_PRECISION, num / i));
-                    }
-                    ind += 1;
-                }
-
-                i += 1;
+            let num = to_integer(parse_node(Rc::clone(&env), &right[0])?)?;
+            if num == 0 {
+                Dynamic::from(Vec::<Int>::new())
+            } else {
+                let mut fac = utils::primes::divisors(&num)?;
+                let len = fac.len();
+                fac.swap(0, len - 1);
+                Dynamic::from(fac)
             }
-
-            let len = fac.len();
-            let temp = fac[len - 1].clone();
-            fac[len - 1] = fac[0].clone();
-            fac[0] = temp;
-
-            Dynamic::from(fac)
         }
 
         // Split array at mid
         "$." => {
-            let mut seq = parse_node(Rc::clone(&env), &right[0]).literal_array();
+            let mut seq = parse_node(Rc::clone(&env), &right[0])?.literal_array();
             seq.set_env(Rc::clone(&env));
-            let seq = seq.collect::<Vec<_>>();
+            if !seq.is_finite() {
+                return Err(ArnError::infinite("$."));
+            }
+            let seq = seq.collect::<ArnResult<Vec<_>>>()?;
 
             Dynamic::from([
                 seq[..seq.len() / 2].to_owned(),
@@ -760,38 +783,29 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
 
         // Evaluate <right> as arn code
         "!." => {
-            let program = parse_node(Rc::clone(&env), &right[0]).literal_string();
-            parse_node(Rc::clone(&env), &crate::build_ast(&program)[0])
+            let program = parse_node(Rc::clone(&env), &right[0])?.literal_string();
+            crate::build_ast(&program)
+                .and_then(|ast| parse_node(Rc::clone(&env), ast.first().unwrap_or(&DEFAULT)))
+                .map_err(ArnError::without_span)?
         }
 
         // Zip <left> and <right>
-        "z" => {
-            let left = parse_node(Rc::clone(&env), &left[0])
-                .literal_array()
-                .set_env_self(Rc::clone(&env))
-                .collect::<Vec<_>>();
-            let right = parse_node(Rc::clone(&env), &right[0])
-                .literal_array()
-                .set_env_self(Rc::clone(&env))
-                .collect::<Vec<_>>();
-            let mut output = Vec::with_capacity(left.len());
-
-            for i in 0..left.len() {
-                output.push([left[i].clone(), right[i].clone()]);
-            }
-
-            Dynamic::from(output)
-        }
+        "z" => zip(&env, &left[0], &right[0])?,
 
         // Dedup <right>
         "#>" => {
+            #[allow(clippy::mutable_key_type)]
             let mut hash = std::collections::HashSet::new();
             let mut result = Vec::new();
-            let array = parse_node(Rc::clone(&env), &right[0])
+            let array = parse_node(Rc::clone(&env), &right[0])?
                 .literal_array()
                 .set_env_self(Rc::clone(&env));
+            if !array.is_finite() {
+                return Err(ArnError::infinite(op));
+            }
             for item in array {
-                if hash.get(&item).is_none() {
+                let item = item?;
+                if !hash.contains(&item) {
                     hash.insert(item.clone());
                     result.push(item);
                 }
@@ -802,41 +816,55 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
 
         // Dedup sieve
         "#:" => {
+            #[allow(clippy::mutable_key_type)]
             let mut hash = std::collections::HashSet::new();
             let mut result = Vec::new();
-            let array = parse_node(Rc::clone(&env), &right[0])
+            let array = parse_node(Rc::clone(&env), &right[0])?
                 .literal_array()
                 .set_env_self(Rc::clone(&env));
+            if !array.is_finite() {
+                return Err(ArnError::infinite(op));
+            }
             for item in array {
-                if hash.get(&item).is_none() {
-                    result.push(Num::with_val(*FLOAT_PRECISION, 1));
-                    hash.insert(item);
+                let item = item?;
+                if hash.contains(&item) {
+                    result.push(Int::new());
                 } else {
-                    result.push(Num::new(*FLOAT_PRECISION));
+                    result.push(Int::from(1));
+                    hash.insert(item);
                 }
             }
 
             Dynamic::from(result)
         }
 
-        // <left>.nth(<right>)
-        "?" => {
-            let mut left = parse_node(Rc::clone(&env), &left[0])
-                .literal_array()
-                .set_env_self(Rc::clone(&env));
-            let index = to_u32(&env, &right[0]) as usize;
-            left.nth(index).unwrap()
+        // First <right> values of <left>
+        ":#" => {
+            let count = to_u32(&env, &right[0])? as usize;
+            Dynamic::from(
+                parse_node(Rc::clone(&env), &left[0])?
+                    .literal_array()
+                    .set_env_self(Rc::clone(&env))
+                    .take(count)
+                    .collect::<ArnResult<Vec<_>>>()?,
+            )
         }
 
+        // <left>.nth(<right>), or the value of key <right> if <left> is a map
+        "?" => nth(&env, &left[0], &right[0])?,
+
+        // <left>[:<right>], <left>[<right>:], <left>[::<right>], <left>[<r1>:<r2>:<r3>]
+        "?<" | "?>" | "?%" | ":?" => slice_op(&env, op, &left[0], &right[0])?,
+
         // Concat <left> and <right>, special case for arrays
         "|" => {
-            let left = parse_node(Rc::clone(&env), &left[0]);
-            let right = parse_node(Rc::clone(&env), &right[0]);
+            let left = parse_node(Rc::clone(&env), &left[0])?;
+            let right = parse_node(Rc::clone(&env), &right[0])?;
             if left.is_array() {
                 let mut left = left
                     .literal_array()
                     .set_env_self(Rc::clone(&env))
-                    .collect::<Vec<_>>();
+                    .collect::<ArnResult<Vec<_>>>()?;
                 if right.is_array() {
                     Dynamic::from(
                         [
@@ -844,7 +872,7 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
                             right
                                 .literal_array()
                                 .set_env_self(Rc::clone(&env))
-                                .collect::<Vec<_>>(),
+                                .collect::<ArnResult<Vec<_>>>()?,
                         ]
                         .concat(),
                     )
@@ -856,7 +884,7 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
                 let mut right = right
                     .literal_array()
                     .set_env_self(Rc::clone(&env))
-                    .collect::<Vec<_>>();
+                    .collect::<ArnResult<Vec<_>>>()?;
                 right.insert(0, left);
                 Dynamic::from(right)
             } else {
@@ -869,42 +897,46 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
         // Very weakly typed, see `src/utils/types.rs`, PartialEq for Dynamic
         // <left> == <right>
         "=" => Dynamic::from(
-            parse_node(Rc::clone(&env), &left[0]) == parse_node(Rc::clone(&env), &right[0]),
+            parse_node(Rc::clone(&env), &left[0])? == parse_node(Rc::clone(&env), &right[0])?,
         ),
 
         // <left> != <right>
         "!=" => Dynamic::from(
-            parse_node(Rc::clone(&env), &left[0]) != parse_node(Rc::clone(&env), &right[0]),
+            parse_node(Rc::clone(&env), &left[0])? != parse_node(Rc::clone(&env), &right[0])?,
         ),
 
         // <left> < <right>
         "<" => Dynamic::from(
-            parse_node(Rc::clone(&env), &left[0]).literal_num()
-                < parse_node(Rc::clone(&env), &right[0]).literal_num(),
+            parse_node(Rc::clone(&env), &left[0])?
+                .cmp_num(&parse_node(Rc::clone(&env), &right[0])?)
+                == Some(Ordering::Less),
         ),
 
         // <left> <= <right>
         "<=" => Dynamic::from(
-            parse_node(Rc::clone(&env), &left[0]).literal_num()
-                <= parse_node(Rc::clone(&env), &right[0]).literal_num(),
+            parse_node(Rc::clone(&env), &left[0])?
+                .cmp_num(&parse_node(Rc::clone(&env), &right[0])?)
+                .is_some_and(Ordering::is_le),
         ),
 
         // <left> > <right>
         ">" => Dynamic::from(
-            parse_node(Rc::clone(&env), &left[0]).literal_num()
-                > parse_node(Rc::clone(&env), &right[0]).literal_num(),
+            parse_node(Rc::clone(&env), &left[0])?
+                .cmp_num(&parse_node(Rc::clone(&env), &right[0])?)
+                == Some(Ordering::Greater),
         ),
 
         // <left> >= <right>
         ">=" => Dynamic::from(
-            parse_node(Rc::clone(&env), &left[0]).literal_num()
-                >= parse_node(Rc::clone(&env), &right[0]).literal_num(),
+            parse_node(Rc::clone(&env), &left[0])?
+                .cmp_num(&parse_node(Rc::clone(&env), &right[0])?)
+                .is_some_and(Ordering::is_ge),
         ),
 
         // <left> && <right> yields <right> if both truthy
         "&&" => {
-            let left = parse_node(Rc::clone(&env), &left[0]);
-            let right = parse_node(Rc::clone(&env), &right[0]);
+            let left = parse_node(Rc::clone(&env), &left[0])?;
+            let right = parse_node(Rc::clone(&env), &right[0])?;
 
             if left.is_bool() {
                 if right.is_bool() {
@@ -925,8 +957,8 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
 
         // <left> || <right> Yields <left> if truthy and <right> otherwise
         "||" => {
-            let left = parse_node(Rc::clone(&env), &left[0]);
-            let right = parse_node(Rc::clone(&env), &right[0]);
+            let left = parse_node(Rc::clone(&env), &left[0])?;
+            let right = parse_node(Rc::clone(&env), &right[0])?;
 
             if left.clone().literal_bool() {
                 left
@@ -939,34 +971,34 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
         ":" => {
             let child_env = Rc::new(env.as_ref().clone());
             if let Node::Block(_, name) = &left[0] {
-                let val = child_env.borrow().get_var("_");
+                let val = child_env.borrow().get_var("_")?;
                 child_env
                     .borrow_mut()
-                    .define_var(name.as_ref().unwrap_or(&USCORE), val)
+                    .define_var(name.as_ref().unwrap_or(&USCORE), val);
             }
-            let mut block = parse_node_uniq(Rc::clone(&child_env), &left[0]);
+            let mut block = parse_node_uniq(Rc::clone(&child_env), &left[0])?;
 
             while {
                 let child_env = Rc::new(env.as_ref().clone());
                 if let Node::Block(_, name) = &right[0] {
                     child_env
                         .borrow_mut()
-                        .define_var(name.as_ref().unwrap_or(&USCORE), block.clone())
+                        .define_var(name.as_ref().unwrap_or(&USCORE), block.clone());
                 } else {
                     child_env.borrow_mut().define_var("_", block.clone());
                 }
 
-                parse_node_uniq(Rc::clone(&child_env), &right[0]).literal_bool()
+                parse_node_uniq(Rc::clone(&child_env), &right[0])?.literal_bool()
             } {
                 if let Node::Block(_, name) = &left[0] {
                     child_env
                         .borrow_mut()
-                        .define_var(name.as_ref().unwrap_or(&USCORE), block.clone())
+                        .define_var(name.as_ref().unwrap_or(&USCORE), block.clone());
                 } else {
                     child_env.borrow_mut().define_var("_", block.clone());
                 }
 
-                block = parse_node_uniq(Rc::clone(&child_env), &left[0]);
+                block = parse_node_uniq(Rc::clone(&child_env), &left[0])?;
             }
 
             block
@@ -975,20 +1007,21 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
         // Compare adjacent values in array <left> and, if <right> evaluates to true, groups them
         "::" => {
             let mut groups = Vec::new();
-            let orig = parse_node(Rc::clone(&env), &left[0])
+            let orig = parse_node(Rc::clone(&env), &left[0])?
                 .literal_array()
                 .set_env_self(Rc::clone(&env));
 
             for node in orig {
+                let node = node?;
                 if groups.last().is_none() {
                     groups.push(vec![node]);
                 } else {
                     let mut vals = vec![
-                        groups.last().unwrap().last().unwrap().clone().into_node(),
-                        node.clone().into_node(),
+                        groups.last().unwrap().last().unwrap().clone().into_node()?,
+                        node.clone().into_node()?,
                     ];
-                    let block = utils::traverse_replace(&mut vals, right[0].clone());
-                    if parse_node(Rc::clone(&env), &block).literal_bool() {
+                    let block = utils::traverse_replace(&mut vals, right[0].clone())?;
+                    if parse_node(Rc::clone(&env), &block)?.literal_bool() {
                         groups.last_mut().unwrap().push(node);
                     } else {
                         groups.push(vec![node]);
@@ -1001,20 +1034,20 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
 
         // If <r1> then bind <r2> to <left>, else yield <left>
         "??" => {
-            let val = parse_node(Rc::clone(&env), &left[0]);
-            let condition = parse_node(Rc::clone(&env), &right[0]).literal_bool();
+            let val = parse_node(Rc::clone(&env), &left[0])?;
+            let condition = parse_node(Rc::clone(&env), &right[0])?.literal_bool();
             let child_env = Rc::new(env.as_ref().clone());
 
             if condition {
                 if let Node::Block(_, name) = &right[1] {
                     child_env
                         .borrow_mut()
-                        .define_var(name.as_ref().unwrap_or(&USCORE), val.clone())
+                        .define_var(name.as_ref().unwrap_or(&USCORE), val.clone());
                 } else {
                     child_env.borrow_mut().define_var("_", val.clone());
                 }
 
-                parse_node(Rc::clone(&child_env), &right[1])
+                parse_node(Rc::clone(&child_env), &right[1])?
             } else {
                 val
             }
@@ -1022,79 +1055,581 @@ pub fn parse_op(env: Env, op: &str, left: &[Node], right: &[Node]) -> Dynamic {
 
         // Bind <right> to each value in <left>
         "@" => {
-            let seq = parse_node(Rc::clone(&env), &left[0])
+            let seq = parse_node(Rc::clone(&env), &left[0])?
                 .literal_array()
                 .set_env_self(Rc::clone(&env));
             let child_env = Rc::new(env.as_ref().clone());
+            if !seq.is_finite() {
+                return Ok(Dynamic::from(Sequence::lazy(Lazy::Map(
+                    Box::new(seq),
+                    right[0].clone(),
+                    child_env,
+                ))));
+            }
 
             Dynamic::from(
-                seq.map(|val| {
-                    if let Node::Block(_, name) = &right[0] {
-                        child_env
-                            .borrow_mut()
-                            .define_var(name.as_ref().unwrap_or(&USCORE), val)
-                    } else {
-                        child_env.borrow_mut().define_var("_", val);
-                    }
+                seq.map(|val| call(&child_env, &right[0], val?))
+                    .collect::<ArnResult<Vec<_>>>()?,
+            )
+        }
 
-                    parse_node_uniq(Rc::clone(&child_env), &right[0])
-                })
-                .collect::<Vec<_>>(),
+        // Map of <right> applied to each value in <left>, to the values it gave that result for
+        ":g" => {
+            let arr = parse_node(Rc::clone(&env), &left[0])?
+                .literal_array()
+                .set_env_self(Rc::clone(&env));
+            let child_env = Rc::new(env.as_ref().clone());
+            Dynamic::from(
+                group_by(arr, ":g", |val| call(&child_env, &right[0], val.clone()))?
+                    .into_iter()
+                    .map(|(key, group)| (key, Dynamic::from(group)))
+                    .collect::<Map>(),
             )
         }
 
         // Bind <right> to <left>
         "&" => {
-            let left = parse_node(Rc::clone(&env), &left[0]);
+            let left = parse_node(Rc::clone(&env), &left[0])?;
             let child_env = Rc::new(env.as_ref().clone());
 
             if let Node::Block(_, name) = &right[0] {
                 child_env
                     .borrow_mut()
-                    .define_var(name.as_ref().unwrap_or(&USCORE), left)
+                    .define_var(name.as_ref().unwrap_or(&USCORE), left);
             } else {
                 child_env.borrow_mut().define_var("_", left);
             }
 
-            parse_node_uniq(Rc::clone(&child_env), &right[0])
+            parse_node_uniq(Rc::clone(&child_env), &right[0])?
         }
 
         // Count of entries in <r2> that, when bound by <r1>, yield a truthy value
         "/:" => {
-            let array = parse_node(Rc::clone(&env), &right[1])
+            let array = parse_node(Rc::clone(&env), &right[1])?
                 .literal_array()
                 .set_env_self(Rc::clone(&env));
+            if !array.is_finite() {
+                return Err(ArnError::infinite("/:"));
+            }
             let child_env = Rc::new(env.as_ref().clone());
 
-            Dynamic::from(Num::with_val(
-                *FLOAT_PRECISION,
-                array
-                    .filter(|val| {
-                        if let Node::Block(_, name) = &right[0] {
-                            child_env
-                                .borrow_mut()
-                                .define_var(name.as_ref().unwrap_or(&USCORE), val.clone())
-                        } else {
-                            child_env.borrow_mut().define_var("_", val.clone());
-                        }
-
-                        parse_node_uniq(Rc::clone(&child_env), &right[0]).literal_bool()
-                    })
-                    .count(),
-            ))
-        }
-
-        _ => unimplemented!(),
+            let mut count = 0;
+            for val in array {
+                if call(&child_env, &right[0], val?)?.literal_bool() {
+                    count += 1;
+                }
+            }
+
+            Dynamic::from(Int::from(count))
+        }
+
+        _ => return Err(ArnError::UnknownOperator(op.to_owned())),
+    })
+}
+
+// Pairs up the values of <left> and <right>, lazily if <left> is infinite
+// Kept out of `parse_op`, whose frame is paid for by every nested operator
+fn zip(env: &Env, left: &Node, right: &Node) -> ArnResult<Dynamic> {
+    let left = parse_node(Rc::clone(env), left)?
+        .literal_array()
+        .set_env_self(Rc::clone(env));
+    let right = parse_node(Rc::clone(env), right)?
+        .literal_array()
+        .set_env_self(Rc::clone(env));
+    if !left.is_finite() {
+        if right.is_finite() {
+            return Err(ArnError::IndexOutOfRange(
+                "cannot zip an infinite sequence with a finite one".to_owned(),
+            ));
+        }
+        return Ok(Dynamic::from(Sequence::lazy(Lazy::Zip(
+            Box::new(left),
+            Box::new(right),
+        ))));
+    }
+
+    let left = left.collect::<ArnResult<Vec<_>>>()?;
+    // Only as many values as there are on the left are needed
+    let right = right.take(left.len()).collect::<ArnResult<Vec<_>>>()?;
+    if right.len() < left.len() {
+        return Err(ArnError::IndexOutOfRange(format!(
+            "cannot zip {} values with {}",
+            left.len(),
+            right.len()
+        )));
+    }
+
+    Ok(Dynamic::from(
+        left.into_iter()
+            .zip(right)
+            .map(|(l, r)| [l, r])
+            .collect::<Vec<_>>(),
+    ))
+}
+
+// How many terms <seq> has, for <op> which needs to know
+fn sequence_len(seq: &Sequence, op: &str) -> ArnResult<usize> {
+    seq.len().ok_or_else(|| {
+        if seq.is_uncounted() {
+            ArnError::uncounted(op)
+        } else {
+            ArnError::infinite(op)
+        }
+    })
+}
+
+// Indexes into <seq> with <index>, looking it up as a key if <seq> is a map
+fn nth(env: &Env, seq: &Node, index: &Node) -> ArnResult<Dynamic> {
+    let seq = parse_node(Rc::clone(env), seq)?;
+    if seq.is_map() {
+        let key = parse_node(Rc::clone(env), index)?;
+        return seq.literal_map()?.get(&key).cloned().ok_or_else(|| {
+            ArnError::IndexOutOfRange(format!("no value for key `{}`", key.entry_string()))
+        });
+    }
+
+    let index = to_index(parse_node(Rc::clone(env), index)?)?;
+    let mut seq = seq.literal_array().set_env_self(Rc::clone(env));
+    let missing = || {
+        ArnError::IndexOutOfRange(format!(
+            "no value at index {}",
+            index.to_string().replace('-', "_")
+        ))
+    };
+
+    // Negative indices count back from the end
+    let pos = if index < 0 {
+        let len = sequence_len(&seq, "?")?;
+        usize::try_from(len as i64 + index).map_err(|_| missing())?
+    } else {
+        index as usize
+    };
+    seq.nth(pos).ok_or_else(missing)?
+}
+
+// Reads <val> as a position in a sequence, which may be negative
+fn to_index(val: Dynamic) -> ArnResult<i64> {
+    let num = val.literal_num();
+    num.clone()
+        .floor()
+        .to_integer()
+        .and_then(|n| n.to_i64())
+        .ok_or_else(|| ArnError::Type(format!("`{}` is not a valid index", Dynamic::from(num))))
+}
+
+// Reads the bounds of `?<`, `?>`, `?%` or `:?` from <bounds>, then slices <seq> with them
+fn slice_op(env: &Env, op: &str, seq: &Node, bounds: &Node) -> ArnResult<Dynamic> {
+    let seq = parse_node(Rc::clone(env), seq)?;
+    let bounds = parse_node(Rc::clone(env), bounds)?;
+    let (start, end, step) = match op {
+        "?<" => (None, Some(to_index(bounds)?), 1),
+        "?>" => (Some(to_index(bounds)?), None, 1),
+        "?%" => (None, None, to_index(bounds)?),
+        _ => {
+            // [start end step], an empty string leaves a bound open
+            let bounds = bounds
+                .literal_array()
+                .set_env_self(Rc::clone(env))
+                .take(4)
+                .map(|b| {
+                    let b = b?;
+                    if b.is_string() && b.clone().literal_string().is_empty() {
+                        Ok(None)
+                    } else {
+                        to_index(b).map(Some)
+                    }
+                })
+                .collect::<ArnResult<Vec<_>>>()?;
+            if bounds.len() > 3 {
+                return Err(ArnError::Type(
+                    "`:?` takes at most a start, an end and a step".to_owned(),
+                ));
+            }
+
+            let bound = |i: usize| bounds.get(i).copied().flatten();
+            (bound(0), bound(1), bound(2).unwrap_or(1))
+        }
+    };
+
+    slice(env, seq, op, start, end, step)
+}
+
+// Every <step>th value of <val> from <start> up to <end>, negative bounds count back from the end
+// Strings are sliced by character, infinite or uncounted sequences and the starts of finite ones lazily
+// (as long as nothing is negative)
+fn slice(
+    env: &Env,
+    val: Dynamic,
+    op: &str,
+    start: Option<i64>,
+    end: Option<i64>,
+    step: i64,
+) -> ArnResult<Dynamic> {
+    if step == 0 {
+        return Err(ArnError::Type(format!("`{op}` cannot step by 0")));
+    }
+
+    if val.is_string() {
+        let chars = val.literal_string().chars().collect::<Vec<_>>();
+        return Ok(Dynamic::from(
+            slice_positions(chars.len(), start, end, step)
+                .map(|i| chars[i])
+                .collect::<String>(),
+        ));
+    }
+
+    let seq = val.literal_array().set_env_self(Rc::clone(env));
+    // The start of a finite sequence is sliced lazily too, so the rest is never computed
+    let prefix = seq
+        .len()
+        .filter(|_| step > 0 && start.unwrap_or(0) >= 0 && end.is_some_and(|end| end >= 0));
+    let uncounted = seq.is_uncounted();
+    if !seq.is_finite() || uncounted || prefix.is_some() {
+        let negative = || {
+            if uncounted {
+                ArnError::uncounted(op)
+            } else {
+                ArnError::InfiniteSequence(format!(
+                    "`{op}` cannot count back from the end of an infinite sequence"
+                ))
+            }
+        };
+        let start = usize::try_from(start.unwrap_or(0)).map_err(|_| negative())?;
+        let step = usize::try_from(step).map_err(|_| negative())?;
+        let lazy = Lazy::Slice(Box::new(seq), start, step);
+        let sliced = if uncounted {
+            Sequence::lazy_uncounted(lazy)
+        } else {
+            Sequence::lazy(lazy)
+        };
+
+        return Ok(match end {
+            Some(end) => {
+                let end = usize::try_from(end).map_err(|_| negative())?;
+                let end = prefix.map_or(end, |len| end.min(len));
+                let count = end.saturating_sub(start).div_ceil(step);
+                Dynamic::from(sliced.take(count).collect::<ArnResult<Vec<_>>>()?)
+            }
+            None => Dynamic::from(sliced),
+        });
+    }
+
+    let values = seq.collect::<ArnResult<Vec<_>>>()?;
+    Ok(Dynamic::from(
+        slice_positions(values.len(), start, end, step)
+            .map(|i| values[i].clone())
+            .collect::<Vec<_>>(),
+    ))
+}
+
+// Positions a slice picks out of <len> values, the same as Python's `[start:end:step]`
+fn slice_positions(
+    len: usize,
+    start: Option<i64>,
+    end: Option<i64>,
+    step: i64,
+) -> impl Iterator<Item = usize> {
+    let len = len as i64;
+    let resolve = |n: i64, low: i64, high: i64| if n < 0 { n + len } else { n }.clamp(low, high);
+    let (start, end) = if step > 0 {
+        (
+            start.map_or(0, |n| resolve(n, 0, len)),
+            end.map_or(len, |n| resolve(n, 0, len)),
+        )
+    } else {
+        // Walking backwards, `end` can be one before the first value
+        (
+            start.map_or(len - 1, |n| resolve(n, -1, len - 1)),
+            end.map_or(-1, |n| resolve(n, -1, len - 1)),
+        )
+    };
+
+    std::iter::successors(Some(start), move |i| Some(i + step))
+        .take_while(move |&i| if step > 0 { i < end } else { i > end })
+        .map(|i| i as usize)
+}
+
+// The prefix arithmetic operators (square, square root, double and half)
+fn unary_arithmetic(op: &str, val: Dynamic) -> Dynamic {
+    match op {
+        ":*" => val
+            .mutate_complex(Cpx::square)
+            .unwrap_or_else(|| val.mutate_exact(|n| Some(n.square()), Num::square)),
+        // Complex when <val> is negative
+        ":/" if val.is_complex() || val.clone().literal_num() < 0 => {
+            Dynamic::from(val.literal_complex().sqrt())
+        }
+        ":/" => val.mutate_exact(
+            |n| {
+                let (num, den) = n.into_numer_denom();
+                (num.is_perfect_square() && den.is_perfect_square())
+                    .then(|| Rat::from((num.sqrt(), den.sqrt())))
+            },
+            Num::sqrt,
+        ),
+        ":+" => val
+            .mutate_complex(|n| n * 2)
+            .unwrap_or_else(|| val.mutate_exact(|n| Some(n * 2), |n| n * 2)),
+        _ => val
+            .mutate_complex(|n| n / 2)
+            .unwrap_or_else(|| val.mutate_exact(|n| keep_exact(n / 2), |n| n / 2)),
+    }
+}
+
+// <left> ^ <right>, exact whenever the result is
+fn power(env: &Env, left: &Node, right: &Node) -> ArnResult<Dynamic> {
+    let left = parse_node(Rc::clone(env), left)?;
+    if left.is_string() {
+        let count = to_u32(env, right)? as usize;
+        return Ok(left.mutate_string(|s| s.repeat(count)));
+    }
+
+    let right = parse_node(Rc::clone(env), right)?.into_num();
+    // Negative numbers only have complex roots
+    let negative_root = left.clone().literal_num() < 0 && !right.clone().literal_num().is_integer();
+    if left.is_complex() || right.is_complex() || negative_root {
+        return Ok(Dynamic::from(
+            left.literal_complex().pow(right.literal_complex()),
+        ));
+    }
+
+    if let (Some(base), Some(exp)) = (left.clone().literal_rat(), right.clone().literal_rat()) {
+        if let Some(n) = exact_power(base, &exp).and_then(keep_exact) {
+            return Ok(Dynamic::from(n));
+        }
+    }
+    Ok(left.mutate_num(|n| n.pow(right.literal_num())))
+}
+
+// <base> ^ <exp> if the root it takes is exact, such as 8 ^ (2/3)
+fn exact_power(base: Rat, exp: &Rat) -> Option<Rat> {
+    let power = exp.numer().to_i32()?;
+    let root = exp.denom().to_u32()?;
+    if base == 0 && power < 0 {
+        return None;
+    }
+
+    let (num, den) = base.into_numer_denom();
+    let (num, num_rem) = num.root_rem(Int::new(), root);
+    let (den, den_rem) = den.root_rem(Int::new(), root);
+    (num_rem == 0 && den_rem == 0).then(|| Rat::from((num, den)).pow(power))
+}
+
+// The integer part of <val>, exact for integers of any size
+fn to_integer(val: Dynamic) -> ArnResult<Int> {
+    if let Some(n) = val.clone().literal_int() {
+        return Ok(n);
+    }
+    let n = val.literal_num();
+    n.clone()
+        .trunc()
+        .to_integer()
+        .ok_or_else(|| ArnError::Type(format!("`{}` is not a valid integer", Dynamic::from(n))))
+}
+
+// Converts <left> between bases as described by <right>, see `base::parse_conversions`
+fn convert_base(env: &Env, left: &Node, right: &Node) -> ArnResult<Dynamic> {
+    let ops = if env.borrow().vals.contains_key(&right.to_string()) {
+        parse_node(Rc::clone(env), right)?.to_string()
+    } else {
+        right.to_string()
+    };
+    let conversions = base::parse_conversions(ops.trim().trim_matches('"'))?;
+    let first = conversions.first();
+
+    let cur = parse_node(Rc::clone(env), left)?;
+    let cur = if cur.is_array() && first.is_some_and(|c| matches!(c, Conversion::FromDigits(_))) {
+        let seq = cur.literal_array().set_env_self(Rc::clone(env));
+        if !seq.is_finite() {
+            return Err(ArnError::infinite(";"));
+        }
+        Dynamic::from(seq.collect::<ArnResult<Vec<_>>>()?)
+    } else if cur.to_string().contains('\n') {
+        // Each line is converted on its own, unless they are the digits of one number
+        let lines = cur
+            .to_string()
+            .trim()
+            .lines()
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        if first.is_some_and(Conversion::reads_number) {
+            Dynamic::from(lines.join(""))
+        } else {
+            Dynamic::from(lines)
+        }
+    } else {
+        cur.into_string()
+    };
+
+    conversions
+        .iter()
+        .try_fold(cur, |cur, conversion| conversion.apply(cur))
+}
+
+// The matrix operators, which work on sequences of rows
+fn matrix_op(env: &Env, op: &str, left: &Node, right: &Node) -> ArnResult<Dynamic> {
+    let left = parse_node(Rc::clone(env), left)?;
+    Ok(match op {
+        ".*" => {
+            let right = parse_node(Rc::clone(env), right)?;
+            matrix::into_dynamic(matrix::multiply(
+                &matrix::to_matrix(env, left, op)?,
+                &matrix::to_matrix(env, right, op)?,
+                op,
+            )?)
+        }
+        ".^" => {
+            let m = matrix::to_matrix(env, left, op)?;
+            let exp = to_integer(parse_node(Rc::clone(env), right)?)?;
+            // A negative power is a power of the inverse
+            let m = if exp < 0 { matrix::inverse(m, op)? } else { m };
+            let exp = Int::from(exp.abs_ref()).to_u32().ok_or_else(|| {
+                ArnError::Type(format!(
+                    "`{op}` cannot raise a matrix to the power `{}`",
+                    Dynamic::from(exp)
+                ))
+            })?;
+            matrix::into_dynamic(matrix::power(m, exp, op)?)
+        }
+        _ => {
+            let right = parse_node(Rc::clone(env), right)?;
+            matrix::dot(
+                &matrix::to_vector(env, left, op)?,
+                &matrix::to_vector(env, right, op)?,
+                op,
+            )?
+        }
+    })
+}
+
+// `:P` and `:B`, which list arrangements of a sequence or count them for a number
+fn pick(env: &Env, op: &str, left: &Node, right: &Node) -> ArnResult<Dynamic> {
+    let left = parse_node(Rc::clone(env), left)?;
+    let k = to_integer(parse_node(Rc::clone(env), right)?)?;
+    if !left.is_array() && !left.is_string() {
+        let n = to_integer(left)?;
+        return if op == ":P" {
+            combinatorics::arrange(&n, &k, op)
+        } else {
+            combinatorics::choose(&n, &k, op)
+        }
+        .map(Dynamic::from);
     }
+
+    let values = matrix::to_vector(env, left, op)?;
+    let k = k.to_usize().ok_or_else(|| {
+        ArnError::Type(format!("`{op}` cannot pick `{}` values", Dynamic::from(k)))
+    })?;
+    Ok(combinatorics::sequence(if op == ":P" {
+        Arrangement::Permutations(values, k)
+    } else {
+        Arrangement::Combinations(values, k)
+    }))
+}
+
+// Takes apart a complex number, real numbers have an imaginary part of 0
+fn complex_part(op: &str, val: Dynamic) -> Dynamic {
+    if !val.is_complex() {
+        let val = val.into_num();
+        return match op {
+            ":I" => Dynamic::from(Int::from(0)),
+            ":A" => Dynamic::from(val.literal_complex().arg()),
+            _ => val,
+        };
+    }
+
+    let n = val.literal_complex();
+    match op {
+        ":R" => Dynamic::from(n.into_real_imag().0).into_exact(),
+        ":I" => Dynamic::from(n.into_real_imag().1).into_exact(),
+        ":C" => Dynamic::from(n.conj()),
+        _ => Dynamic::from(n.arg()),
+    }
+}
+
+// Splits the values of <seq> into groups with equal keys, in the order each key first appears
+fn group_by<F>(seq: Sequence, op: &str, mut key: F) -> ArnResult<Vec<(Dynamic, Vec<Dynamic>)>>
+where
+    F: FnMut(&Dynamic) -> ArnResult<Dynamic>,
+{
+    if !seq.is_finite() {
+        return Err(ArnError::infinite(op));
+    }
+
+    #[allow(clippy::mutable_key_type)]
+    let mut positions = std::collections::HashMap::<Dynamic, usize>::new();
+    let mut groups: Vec<(Dynamic, Vec<Dynamic>)> = Vec::new();
+    for val in seq {
+        let val = val?;
+        let key = key(&val)?;
+        if let Some(&pos) = positions.get(&key) {
+            groups[pos].1.push(val);
+        } else {
+            positions.insert(key.clone(), groups.len());
+            groups.push((key, vec![val]));
+        }
+    }
+
+    Ok(groups)
+}
+
+// Evaluates the keys and values of a map literal, which alternate in <body>
+fn map_literal(env: &Env, body: &[Node]) -> ArnResult<Dynamic> {
+    if body.len() % 2 == 1 {
+        return Err(ArnError::Type(
+            "every key in a map literal needs a value".to_owned(),
+        ));
+    }
+
+    let vals = body
+        .iter()
+        .map(|n| parse_node(Rc::clone(env), n))
+        .collect::<ArnResult<Vec<_>>>()?;
+    Ok(Dynamic::from(
+        vals.chunks(2)
+            .map(|pair| (pair[0].clone(), pair[1].clone()))
+            .collect::<Map>(),
+    ))
+}
+
+// Values of <seq> up to the first that <f> isn't truthy for
+fn take_while(env: &Env, f: &Node, seq: &Node) -> ArnResult<Dynamic> {
+    let seq = parse_node(Rc::clone(env), seq)?
+        .literal_array()
+        .set_env_self(Rc::clone(env));
+    let child_env = Rc::new(env.as_ref().clone());
+
+    let mut taken = Vec::new();
+    for v in seq {
+        let v = v?;
+        if !call(&child_env, f, v.clone())?.literal_bool() {
+            break;
+        }
+        taken.push(v);
+    }
+
+    Ok(Dynamic::from(taken))
+}
+
+// Binds <val> to the key of <f> (`_` for anything but a named block) in <env>, then evaluates <f>
+pub(crate) fn call(env: &Env, f: &Node, val: Dynamic) -> ArnResult<Dynamic> {
+    if let Node::Block(_, name) = f {
+        env.borrow_mut()
+            .define_var(name.as_ref().unwrap_or(&USCORE), val);
+    } else {
+        env.borrow_mut().define_var("_", val);
+    }
+
+    parse_node_uniq(Rc::clone(env), f)
 }
 
 // Parses Node::Block, assuming it's key has already been initialized
 // Call if the key is checked before
-fn parse_node_uniq(env: Env, block: &Node) -> Dynamic {
+fn parse_node_uniq(env: Env, block: &Node) -> ArnResult<Dynamic> {
     match block {
         Node::Block(block, _) => {
-            for node in &block[..block.len() - 1] {
-                parse_node(Rc::clone(&env), node);
+            for node in block.iter().take(block.len().saturating_sub(1)) {
+                parse_node(Rc::clone(&env), node)?;
             }
 
             parse_node(Rc::clone(&env), block.last().unwrap_or(&DEFAULT))
@@ -1104,9 +1639,11 @@ fn parse_node_uniq(env: Env, block: &Node) -> Dynamic {
     }
 }
 
-pub fn parse_node(env: Env, node: &Node) -> Dynamic {
-    match node {
-        Node::Op(op, left, right) => parse_op(env, op, left, right),
+pub fn parse_node(env: Env, node: &Node) -> ArnResult<Dynamic> {
+    Ok(match node {
+        Node::Op(op, left, right, span) => {
+            parse_op(env, op, left, right).map_err(|e| e.with_span(*span))?
+        }
 
         Node::String(v) => Dynamic::from(v.clone()),
 
@@ -1114,29 +1651,38 @@ pub fn parse_node(env: Env, node: &Node) -> Dynamic {
 
         Node::Number(v) => Dynamic::from(v.clone()),
 
-        Node::Variable(v) => env
-            .borrow()
-            .attempt_call(v, &env, env.borrow().get_var("_")),
+        Node::Integer(v) => Dynamic::from(v.clone()),
+
+        Node::Rational(v) => Dynamic::from(v.clone()),
+
+        Node::Complex(v) => Dynamic::from(v.as_ref().clone()),
+
+        Node::Variable(v, span) => {
+            let arg = env.borrow().get_var("_")?;
+            env.borrow()
+                .attempt_call(v, &env, arg)
+                .map_err(|e| e.with_span(*span))?
+        }
 
         Node::Group(body) => {
-            for node in &body[..body.len() - 1] {
-                parse_node(Rc::clone(&env), node);
+            for node in body.iter().take(body.len().saturating_sub(1)) {
+                parse_node(Rc::clone(&env), node)?;
             }
 
-            parse_node(Rc::clone(&env), body.last().unwrap_or(&DEFAULT))
+            parse_node(Rc::clone(&env), body.last().unwrap_or(&DEFAULT))?
         }
 
         Node::Block(body, name) => {
             let child_env = Rc::new(env.as_ref().clone());
-            let val = child_env.borrow().get_var("_");
+            let val = child_env.borrow().get_var("_")?;
             child_env
                 .borrow_mut()
                 .define_var(name.as_ref().unwrap_or(&USCORE), val);
-            for node in &body[..body.len() - 1] {
-                parse_node(Rc::clone(&child_env), node);
+            for node in body.iter().take(body.len().saturating_sub(1)) {
+                parse_node(Rc::clone(&child_env), node)?;
             }
 
-            parse_node(Rc::clone(&child_env), body.last().unwrap_or(&DEFAULT))
+            parse_node(Rc::clone(&child_env), body.last().unwrap_or(&DEFAULT))?
         }
 
         // This will maybe be parsed differently in the future?
@@ -1144,15 +1690,20 @@ pub fn parse_node(env: Env, node: &Node) -> Dynamic {
             let mut seq = Sequence::from_vec_dyn(
                 &arr.iter()
                     .map(|n| parse_node(Rc::clone(&env), n))
-                    .collect::<Vec<Dynamic>>(),
+                    .collect::<ArnResult<Vec<Dynamic>>>()?,
                 block.as_ref().clone(),
-                len.as_ref().map(|n| to_u32(&env, n.as_ref()) as usize),
+                match len {
+                    Some(n) => Some(to_u32(&env, n.as_ref())? as usize),
+                    None => None,
+                },
             );
 
             seq.set_env(Rc::clone(&env));
             Dynamic::new(Val::Array(Box::new(seq)), 4)
         }
-    }
+
+        Node::Map(body) => map_literal(&env, body)?,
+    })
 }
 
 macro_rules! def_builtins {
@@ -1161,79 +1712,57 @@ macro_rules! def_builtins {
             $env.define([$($name),*], |e, val| {
                 let child = Rc::new(e.as_ref().clone());
                 child.borrow_mut().define_var("_", val);
-                parse_node(Rc::clone(&child), &crate::build_ast($value)[0])
+                crate::build_ast($value)
+                    .and_then(|ast| parse_node(Rc::clone(&child), &ast[0]))
+                    .map_err(ArnError::without_span)
             });
         )*
     }
 }
 
-pub fn parse(ast: &[Node]) {
-    let mut env = Environment::init();
-
-    let mut stdin = if let Some(val) = MATCHES.value_of("input") {
-        val.to_owned()
-    } else if atty::is(atty::Stream::Stdin) {
-        String::from("")
-    } else {
-        let mut buffer = String::new();
-        io::stdin()
-            .read_to_string(&mut buffer)
-            .expect("Could not read from stdin");
-        buffer.trim_end_matches('\n').to_owned()
-    };
-
-    if MATCHES.is_present("one-ten") {
-        stdin = utils::create_str_range(1, 10);
-    }
-    if MATCHES.is_present("one-hundred") {
-        stdin = utils::create_str_range(1, 100);
-    }
-    if MATCHES.is_present("rangeify") {
-        stdin = utils::create_str_range(
-            1,
-            stdin
-                .parse::<usize>()
-                .expect("Input was not a valid integer"),
-        );
+// Builtins that take a single integer of any size
+macro_rules! def_number_theory {
+    ($env:ident; $($($name:literal),*: $f:expr);*) => {
+        $(
+            $env.define([$($name),*], |_, val| {
+                let f: fn(Int) -> ArnResult<Dynamic> = $f;
+                f(to_integer(val)?)
+            });
+        )*
     }
-    if MATCHES.is_present("0-range") {
-        stdin = utils::create_str_range(
-            0,
-            stdin
-                .parse::<usize>()
-                .expect("Input was not a valid integer")
-                - 1,
-        );
+}
+
+// Combines the integers in the finite sequence <seq>
+fn fold_integers<F>(env: &Env, seq: Dynamic, op: &str, init: Int, f: F) -> ArnResult<Dynamic>
+where
+    F: Fn(Int, Int) -> Int,
+{
+    let seq = seq.literal_array().set_env_self(Rc::clone(env));
+    if !seq.is_finite() {
+        return Err(ArnError::infinite(op));
     }
+    seq.map(|n| to_integer(n?))
+        .collect::<ArnResult<Vec<_>>>()
+        .map(|nums| Dynamic::from(nums.into_iter().fold(init, f)))
+}
 
-    // Defined variables
-    env.define_var(
-        "_",
-        // Eval code as input if `-e` present
-        if MATCHES.is_present("eval") {
-            parse_node(
-                Rc::new(RefCell::new(env.clone())),
-                &crate::build_ast(&stdin)[0],
-            )
-        } else {
-            Dynamic::from(stdin.clone())
-        },
-    );
+// Constants available to every program
+pub fn define_constants(env: &mut Environment) -> ArnResult<()> {
     env.define_var(
         "E",
         Num::with_val(
-            *FLOAT_PRECISION,
+            crate::float_precision(),
             Num::parse("2.7182818284590452353602874713527").unwrap(),
         ),
     );
     env.define_var(
         "pi",
-        Num::with_val(*FLOAT_PRECISION, rug::float::Constant::Pi),
+        Num::with_val(crate::float_precision(), rug::float::Constant::Pi),
     );
     env.define_var(
         "phi",
         Num::with_val(
-            *FLOAT_PRECISION,
+            crate::float_precision(),
             Num::parse("1.61803398874989484820458683436563811").unwrap(),
         ),
     );
@@ -1241,139 +1770,125 @@ pub fn parse(ast: &[Node]) {
     env.define_var("c", String::new());
     env.define_var("Fi", "Fizz".to_string());
     env.define_var("Bu", "Buzz".to_string());
-    let dummy_env = Rc::new(RefCell::new(Environment::init()));
+    // Sequence blocks read `_` before it is replaced with the previous terms
+    let mut dummy_env = Environment::init();
+    dummy_env.define_var("_", String::new());
+    let dummy_env = Rc::new(RefCell::new(dummy_env));
+    let num = |n: f64| Num::with_val(crate::float_precision(), n);
+    let block = |body: &str| crate::build_ast(body).map(|body| Node::Block(body, None));
+    // Exact halves in rational mode
+    let half = keep_exact(Rat::from((1, 2))).map_or_else(|| Dynamic::from(num(0.5)), Dynamic::from);
     env.define_var(
         "sH",
-        Sequence::from_vec(
-            &crate::build_ast("1/2"),
-            crate::build_ast("/2")[0].clone(),
-            None,
-        )
-        .set_env_self(Rc::clone(&dummy_env)),
+        Sequence::from_vec_dyn(&[half], block("/2")?, None).set_env_self(Rc::clone(&dummy_env)),
     );
     env.define_var(
         "sA",
-        Sequence::from_vec(
-            &crate::build_ast("1"),
-            crate::build_ast("!")[0].clone(),
-            None,
-        )
-        .set_env_self(Rc::clone(&dummy_env)),
+        Sequence::from_vec(&[Int::from(1)], block("!")?, None).set_env_self(Rc::clone(&dummy_env)),
     );
     env.define_var(
         "sE",
-        Sequence::from_vec(
-            &crate::build_ast("2"),
-            crate::build_ast("+2")[0].clone(),
-            None,
-        )
-        .set_env_self(Rc::clone(&dummy_env)),
+        Sequence::from_vec(&[Int::from(2)], block("+2")?, None).set_env_self(Rc::clone(&dummy_env)),
     );
     env.define_var(
         "sO",
-        Sequence::from_vec(
-            &crate::build_ast("1"),
-            crate::build_ast("+2")[0].clone(),
-            None,
-        )
-        .set_env_self(Rc::clone(&dummy_env)),
+        Sequence::from_vec(&[Int::from(1)], block("+2")?, None).set_env_self(Rc::clone(&dummy_env)),
     );
     env.define_var(
         "sF",
-        Sequence::from_vec(
-            &crate::build_ast("1 1"),
-            crate::build_ast("+")[0].clone(),
-            None,
-        )
-        .set_env_self(Rc::clone(&dummy_env)),
+        Sequence::from_vec(&[Int::from(1), Int::from(1)], block("+")?, None)
+            .set_env_self(Rc::clone(&dummy_env)),
     );
-    // I don't care what people say, I am never adding a constant for "Hello, World!"
-
-    if MATCHES.is_present("input-left") {
-        let orig = env.get_var("_");
-        let as_vec = orig.literal_array().collect::<Vec<_>>();
-        env.define_var("_", as_vec[0].clone());
-        env.define_var("a", as_vec[1..].iter().cloned().collect::<Vec<_>>());
-    } else if MATCHES.is_present("input-right") {
-        let orig = env.get_var("_");
-        let as_vec = orig.literal_array().collect::<Vec<_>>();
-        env.define_var("_", as_vec[1..].iter().cloned().collect::<Vec<_>>());
-        env.define_var("a", as_vec[0].clone());
+    for (name, named) in [
+        ("sP", Named::Primes),
+        ("sS", Named::Squares),
+        ("sT", Named::Triangular),
+        ("sC", Named::Catalan),
+        ("sf", Named::Factorials),
+        ("s2", Named::PowersOfTwo),
+        ("sL", Named::Lucas),
+    ] {
+        env.define_var(name, Sequence::lazy(Lazy::Named(named, 0, None)));
     }
+    // I don't care what people say, I am never adding a constant for "Hello, World!"
+    Ok(())
+}
 
-    // Defined functions
-    env.define(["ol", "outl"], |_, d| {
-        println!("{}", d);
-        d
+// Functions available to every program
+pub fn define_builtins(env: &mut Environment, output: &Output) {
+    let out = Rc::clone(output);
+    env.define(["ol", "outl"], move |_, d| {
+        io::write_value(&out, &d, "\n")?;
+        Ok(d)
     });
-    env.define(["o", "out"], |_, d| {
-        print!("{}", d);
-        d
+    let out = Rc::clone(output);
+    env.define(["o", "out"], move |_, d| {
+        io::write_value(&out, &d, "")?;
+        Ok(d)
+    });
+    env.define(["det", "determinant"], |e, d| {
+        matrix::determinant(matrix::to_matrix(&e, d, "det")?, "det")
+    });
+    env.define(["inv", "inverse"], |e, d| {
+        matrix::inverse(matrix::to_matrix(&e, d, "inv")?, "inv").map(matrix::into_dynamic)
+    });
+    env.define(["idm", "identity"], |_, d| {
+        let n = d.literal_num();
+        let n = n
+            .to_u32_saturating_round(rug::float::Round::Down)
+            .ok_or_else(|| ArnError::Type(format!("`{n}` is not a valid matrix size")))?;
+        Ok(matrix::into_dynamic(matrix::identity(n as usize)))
+    });
+    def_number_theory! {env;
+        "pf", "factorize":  |n| utils::primes::factorize(&n).map(Dynamic::from);
+        "isp", "isprime":   |n| Ok(Dynamic::from(utils::primes::is_prime(&n)));
+        "tot", "totient":   |n| utils::primes::totient(&n).map(Dynamic::from);
+        "sig", "sigma":     |n| utils::primes::sigma(&n).map(Dynamic::from);
+        "pn", "nthprime":   |n| utils::primes::nth_prime(&n).map(Dynamic::from);
+        "nxp", "nextprime": |n| Ok(Dynamic::from(utils::primes::next_prime(&n)));
+        "pvp", "prevprime": |n| utils::primes::prev_prime(&n).map(Dynamic::from)
+    };
+    env.define(["f", "fact"], |_, d| {
+        combinatorics::factorial(&to_integer(d)?, "f").map(Dynamic::from)
+    });
+    env.define(["prm", "permutations"], |e, d| {
+        let values = matrix::to_vector(&e, d, "prm")?;
+        let k = values.len();
+        Ok(combinatorics::sequence(Arrangement::Permutations(
+            values, k,
+        )))
+    });
+    env.define(["crt", "cartesian"], |e, d| {
+        let lists = matrix::to_vector(&e, d, "crt")?
+            .into_iter()
+            .map(|list| matrix::to_vector(&e, list, "crt"))
+            .collect::<ArnResult<_>>()?;
+        Ok(combinatorics::sequence(Arrangement::Product(lists)))
+    });
+    env.define(["pst", "powerset"], |e, d| {
+        matrix::to_vector(&e, d, "pst")
+            .map(|values| combinatorics::sequence(Arrangement::Subsets(values)))
+    });
+    env.define(["ptn", "partitions"], |_, d| {
+        let n = to_integer(d)?;
+        let n = n
+            .to_u32()
+            .ok_or_else(|| ArnError::Type(format!("`{}` has no partitions", Dynamic::from(n))))?;
+        Ok(combinatorics::sequence(Arrangement::Partitions(n)))
+    });
+    env.define(["gcd"], |e, d| {
+        fold_integers(&e, d, "gcd", Int::new(), |acc, n| acc.gcd(&n))
+    });
+    env.define(["lcm"], |e, d| {
+        fold_integers(&e, d, "lcm", Int::from(1), |acc, n| acc.lcm(&n))
     });
     def_builtins! {env;
-        "f", "fact":        r#"~||[1]&*\"#;
-        "me", "mean":       r#"(+\)/(#"#;
-        "ma", "max":        r#":>&:{"#;
-        "mo", "mode":       r#":@&ma:{"#;
-        "mi", "min":        r#":<&:{"#;
-        "med", "median":    r#"(:-#&%2=0)&&:-(((:<)?(--:-#))+((:<)?:-#))||(:<)?:v:-#"#;
-        "sdev":             r#":/((@v{:*(v-me)).me"#;
-        "crt", "cartesian": r#":{@a{:}@a<>}&:_"#;
-        "eq", "equal":      r#":@#=1"#;
-        "pst", "powerset":  r#":<(0->2^(#);2@a{|{+0&&[:}]||[}\a.<z"#
+        "me", "mean":       r"(+\)/(#";
+        "ma", "max":        r":>&:{";
+        "mo", "mode":       r":@&ma:{";
+        "mi", "min":        r":<&:{";
+        "med", "median":    r"(:-#&%2=0)&&:-(((:<)?(--:-#))+((:<)?:-#))||(:<)?:v:-#";
+        "sdev":             r":/((@v{:*(v-me)).me";
+        "eq", "equal":      r":@#=1"
     };
-
-    let env: Env = Rc::new(RefCell::new(env));
-
-    for node in &ast[..ast.len() - 1] {
-        parse_node(Rc::clone(&env), node);
-    }
-
-    let mut result = parse_node(Rc::clone(&env), ast.last().unwrap_or(&DEFAULT));
-
-    if MATCHES.is_present("first") {
-        result = result
-            .literal_array()
-            .set_env_self(Rc::clone(&env))
-            .collect::<Vec<_>>()
-            .first()
-            .unwrap()
-            .clone();
-    }
-    if MATCHES.is_present("last") {
-        result = result
-            .literal_array()
-            .set_env_self(Rc::clone(&env))
-            .collect::<Vec<_>>()
-            .last()
-            .unwrap()
-            .clone();
-    }
-    if MATCHES.is_present("index") {
-        result = result
-            .literal_array()
-            .set_env_self(Rc::clone(&env))
-            .nth(stdin.parse::<usize>().unwrap())
-            .unwrap();
-    }
-    if MATCHES.is_present("sum") {
-        result = Dynamic::from(
-            result
-                .literal_array()
-                .set_env_self(Rc::clone(&env))
-                .map(|n| n.literal_num())
-                .fold(Num::new(*FLOAT_PRECISION), |acc, val| acc + val),
-        );
-    }
-    if MATCHES.is_present("size") {
-        result = Dynamic::from(Num::with_val(
-            *FLOAT_PRECISION,
-            result.literal_array().set_env_self(Rc::clone(&env)).count(),
-        ));
-    }
-    if MATCHES.is_present("not") {
-        result = Dynamic::from(!result.literal_bool())
-    }
-
-    println!("{}", result);
 }
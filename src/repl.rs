@@ -0,0 +1,148 @@
+use std::fs;
+
+use arn_language::utils::compress;
+use arn_language::{build_ast, lexer, Interpreter, Options, RationalMode};
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+const HELP: &str = "\
+:ast <code>     Prints the syntax tree of <code>
+:tokens <code>  Prints the tokens of <code>
+:load <file>    Runs <file>, keeping anything it defines
+:reset          Forgets every definition
+:precision [<float> [<output>]]
+                Prints or changes the precision of floats (in bits) and of output (in digits)
+:rational [off|fraction|decimal]
+                Prints or changes whether division stays exact, and how fractions are printed
+:help           Prints this message
+.exit           Leaves the shell";
+
+// Interactive shell; values defined with `:=` are kept between lines
+pub fn run(stdin: &str) {
+    let options = Options {
+        stdin: stdin.to_owned(),
+        eval: true,
+        ..Options::default()
+    };
+    let mut arn = match Interpreter::new(options) {
+        Ok(arn) => arn,
+        Err(e) => {
+            eprintln!("{}", e.render(stdin));
+            return;
+        }
+    };
+    let mut editor = DefaultEditor::new().expect("Could not start the line editor");
+
+    while let Some(line) = read_input(&mut editor) {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+
+        let (command, arg) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let arg = arg.trim();
+        match command {
+            ".exit" => break,
+            ":help" => println!("{HELP}"),
+            ":reset" => {
+                if let Err(e) = arn.reset() {
+                    eprintln!("{e}");
+                }
+            }
+            ":precision" => match set_precision(&mut arn, arg) {
+                Ok(()) => {
+                    let precision = arn.precision();
+                    println!("float: {}, output: {}", precision.float, precision.output);
+                }
+                Err(e) => eprintln!("{e}"),
+            },
+            ":rational" => {
+                let mode = match arg {
+                    "" => Some(arn.rational_mode()),
+                    "off" => Some(RationalMode::Off),
+                    "fraction" => Some(RationalMode::Fraction),
+                    "decimal" => Some(RationalMode::Decimal),
+                    _ => None,
+                };
+                match mode {
+                    Some(mode) => {
+                        arn.set_rational_mode(mode);
+                        println!("rational: {}", format!("{mode:?}").to_lowercase());
+                    }
+                    None => eprintln!("`{arg}` is not a rational mode (off, fraction or decimal)"),
+                }
+            }
+            ":ast" => match build_ast(arg) {
+                Ok(ast) => ast.iter().for_each(|node| println!("{node:?}")),
+                Err(e) => eprintln!("{}", e.render(arg)),
+            },
+            ":tokens" => match lexer::lex(arg) {
+                Ok(tokens) => tokens.iter().for_each(|tok| println!("{tok:?}")),
+                Err(e) => eprintln!("{}", e.render(arg)),
+            },
+            ":load" => match fs::read_to_string(arg) {
+                Ok(program) => {
+                    let mut program = program.replace("\r\n", "\n").trim().to_owned();
+                    if compress::is_packed(&program) {
+                        program = compress::unpack(&program);
+                    }
+                    eval(&mut arn, &program);
+                }
+                Err(e) => eprintln!("Could not read '{arg}': {e}"),
+            },
+            _ => eval(&mut arn, line),
+        }
+    }
+}
+
+// Reads one entry, continuing onto new lines while a group is left open
+// Returns `None` once the shell should close
+fn read_input(editor: &mut DefaultEditor) -> Option<String> {
+    let mut input = String::new();
+    loop {
+        let prompt = if input.is_empty() { "> " } else { ". " };
+        match editor.readline(prompt) {
+            Ok(line) => {
+                if !input.is_empty() {
+                    input.push('\n');
+                }
+                input.push_str(&line);
+                if !lexer::is_unclosed(&input) {
+                    return Some(input);
+                }
+            }
+            // Ctrl-C drops whatever has been typed so far
+            Err(ReadlineError::Interrupted) => input.clear(),
+            Err(ReadlineError::Eof) => return None,
+            Err(e) => {
+                eprintln!("{e}");
+                return None;
+            }
+        }
+    }
+}
+
+// Parses `<float> [<output>]`, leaving whatever is omitted unchanged
+fn set_precision(arn: &mut Interpreter, arg: &str) -> Result<(), String> {
+    let mut precision = arn.precision();
+    let mut args = arg.split_whitespace();
+    if let Some(float) = args.next() {
+        precision.float = float
+            .parse()
+            .map_err(|_| format!("`{float}` is not a valid float precision"))?;
+    }
+    if let Some(output) = args.next() {
+        precision.output = output
+            .parse()
+            .map_err(|_| format!("`{output}` is not a valid output precision"))?;
+    }
+
+    arn.set_precision(precision).map_err(|e| e.to_string())
+}
+
+fn eval(arn: &mut Interpreter, program: &str) {
+    if let Err(e) = arn.eval(program).and_then(|result| arn.print(&result)) {
+        eprintln!("{}", e.render(program));
+    }
+}
@@ -0,0 +1,359 @@
+// Conversions between integers and their digits in other bases
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+use super::error::{ArnError, ArnResult};
+use super::num::Int;
+use super::types::Dynamic;
+
+const DIGITS: &str = "0123456789abcdefghijklmnopqrstuvwxyz";
+
+// Bijective bases have no 0
+const BIJECTIVE_DIGITS: &str = "123456789abcdefghijklmnopqrstuvwxyz";
+
+// The digits of base <base>, `0-9` then `a-z`
+pub fn standard(base: u32) -> ArnResult<Vec<char>> {
+    if (2..=36).contains(&base) {
+        Ok(DIGITS.chars().take(base as usize).collect())
+    } else {
+        Err(ArnError::Type(format!(
+            "base {base} is not between 2 and 36"
+        )))
+    }
+}
+
+// A custom set of digits, which must be at least two distinct chars
+pub fn alphabet(digits: &str) -> ArnResult<Vec<char>> {
+    let chars: Vec<char> = digits.chars().collect();
+    if chars.len() < 2 {
+        return Err(ArnError::Type(format!(
+            "the alphabet `{digits}` needs at least two digits"
+        )));
+    }
+    if chars
+        .iter()
+        .enumerate()
+        .any(|(i, c)| chars[..i].contains(c))
+    {
+        return Err(ArnError::Type(format!(
+            "the alphabet `{digits}` repeats a digit"
+        )));
+    }
+    Ok(chars)
+}
+
+// Reads an integer written in base 10, which may start with `_` or `-`
+pub fn parse_int(s: &str) -> ArnResult<Int> {
+    let s = s.trim();
+    let digits = s.strip_prefix(['_', '-']).unwrap_or(s);
+    let invalid = || ArnError::Type(format!("`{s}` is not a valid base 10 number"));
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let n = Int::parse(digits).map(Int::from).map_err(|_| invalid())?;
+    Ok(if digits.len() == s.len() { n } else { -n })
+}
+
+// The digits of <n> in <base>, most significant first
+// Each digit of a negative number is negative
+pub fn to_digits(n: &Int, base: u32) -> Vec<Int> {
+    // GMP is much faster for the bases it can write
+    if (2..=36).contains(&base) {
+        let sign = if *n < 0 { -1 } else { 1 };
+        return Int::from(n.abs_ref())
+            .to_string_radix(base as i32)
+            .chars()
+            .map(|c| Int::from(c.to_digit(base).unwrap() as i32 * sign))
+            .collect();
+    }
+
+    let mut rest = Int::from(n.abs_ref());
+    let mut digits = Vec::new();
+    loop {
+        let (quot, rem) = rest.div_rem(Int::from(base));
+        digits.push(if *n < 0 { -rem } else { rem });
+        rest = quot;
+        if rest == 0 {
+            break;
+        }
+    }
+    digits.reverse();
+    digits
+}
+
+pub fn from_digits(digits: &[Int], base: u32) -> Int {
+    digits
+        .iter()
+        .fold(Int::new(), |acc, digit| acc * base + digit)
+}
+
+// <n> written with <digits>, negative numbers start with `_`
+pub fn to_alphabet(n: &Int, digits: &[char]) -> String {
+    let sign = if *n < 0 { "_" } else { "" };
+    let base = digits.len() as u32;
+    sign.chars()
+        .chain(
+            to_digits(&Int::from(n.abs_ref()), base)
+                .iter()
+                .map(|d| digits[d.to_usize().unwrap()]),
+        )
+        .collect()
+}
+
+// Reads <s> as a number written with <digits>
+pub fn from_alphabet(s: &str, digits: &[char]) -> ArnResult<Int> {
+    let base = digits.len() as u32;
+    // The standard digits are read in either case
+    let standard = digits.iter().copied().eq(DIGITS.chars().take(digits.len()));
+    let invalid = || {
+        if standard {
+            ArnError::Type(format!("`{s}` is not a valid base {base} number"))
+        } else {
+            let name: String = digits.iter().collect();
+            ArnError::Type(format!(
+                "`{s}` is not a valid number in the alphabet `{name}`"
+            ))
+        }
+    };
+
+    let (negative, body) = match s.strip_prefix(['_', '-']) {
+        Some(rest) if !digits.contains(&s.chars().next().unwrap()) => (true, rest),
+        _ => (false, s),
+    };
+    if body.is_empty() {
+        return Err(invalid());
+    }
+
+    let values = body
+        .chars()
+        .map(|c| {
+            let c = if standard { c.to_ascii_lowercase() } else { c };
+            digits
+                .iter()
+                .position(|&d| d == c)
+                .map(Int::from)
+                .ok_or_else(invalid)
+        })
+        .collect::<ArnResult<Vec<_>>>()?;
+    let n = from_digits(&values, base);
+    Ok(if negative { -n } else { n })
+}
+
+fn check_bijective(base: u32) -> ArnResult<()> {
+    if (1..=35).contains(&base) {
+        Ok(())
+    } else {
+        Err(ArnError::Type(format!(
+            "bijective base {base} is not between 1 and 35"
+        )))
+    }
+}
+
+// <n> in bijective base <base>, which uses the digits 1 to <base>
+pub fn to_bijective(n: &Int, base: u32) -> ArnResult<String> {
+    check_bijective(base)?;
+    if *n < 0 {
+        return Err(ArnError::Type(format!(
+            "`{}` cannot be written in bijective base {base}",
+            n.to_string().replace('-', "_")
+        )));
+    }
+
+    let digits: Vec<char> = BIJECTIVE_DIGITS.chars().collect();
+    let mut rest = n.clone();
+    let mut out = Vec::new();
+    while rest > 0 {
+        // The digit is 1 to <base> instead of 0 to <base> - 1
+        rest -= 1;
+        let (quot, rem) = rest.div_rem(Int::from(base));
+        out.push(digits[rem.to_usize().unwrap()]);
+        rest = quot;
+    }
+    Ok(out.iter().rev().collect())
+}
+
+pub fn from_bijective(s: &str, base: u32) -> ArnResult<Int> {
+    check_bijective(base)?;
+    s.chars()
+        .map(|c| {
+            BIJECTIVE_DIGITS
+                .chars()
+                .take(base as usize)
+                .position(|d| d == c.to_ascii_lowercase())
+                .map(|i| Int::from(i + 1))
+                .ok_or_else(|| {
+                    ArnError::Type(format!("`{s}` is not a valid bijective base {base} number"))
+                })
+        })
+        .collect::<ArnResult<Vec<_>>>()
+        .map(|digits| from_digits(&digits, base))
+}
+
+// One step of the conversions done by `;`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conversion {
+    /// Base 10 number to a string of these digits
+    To(Vec<char>),
+
+    /// String of these digits to a number
+    From(Vec<char>),
+
+    /// Number to the list of its digits in a base
+    ToDigits(u32),
+
+    /// List of digits in a base to a number
+    FromDigits(u32),
+
+    /// Number to bijective base
+    ToBijective(u32),
+
+    /// Bijective base to a number
+    FromBijective(u32),
+}
+
+// The base written from <digits> onwards, if there is one
+fn read_base(mut digits: String, chars: &mut Peekable<Chars<'_>>) -> ArnResult<Option<u32>> {
+    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+        digits.push(c);
+    }
+    if digits.is_empty() {
+        return Ok(None);
+    }
+    digits
+        .parse()
+        .map(Some)
+        .map_err(|_| ArnError::Type(format!("base {digits} is too large")))
+}
+
+fn require_base(chars: &mut Peekable<Chars<'_>>, c: char) -> ArnResult<u32> {
+    read_base(String::new(), chars)?
+        .ok_or_else(|| ArnError::Type(format!("`{c}` needs to be followed by a base")))
+}
+
+// Lists of digits are in base 10 unless another base is given
+fn digit_base(chars: &mut Peekable<Chars<'_>>) -> ArnResult<u32> {
+    let base = read_base(String::new(), chars)?.unwrap_or(10);
+    if base >= 2 {
+        Ok(base)
+    } else {
+        Err(ArnError::Type(format!(
+            "base {base} has no digits to split into"
+        )))
+    }
+}
+
+// Reads the conversions written after `;`
+// `b`, `o`, `h` and a plain number convert to base 2, 8, 16 or that number, and `B`, `O`, `H` and `f<n>` convert back
+// `d<n>`/`D<n>` go to and from a list of digits, `j<n>`/`J<n>` to and from bijective base
+// `a`/`A` use the rest of the string as the digits
+pub fn parse_conversions(ops: &str) -> ArnResult<Vec<Conversion>> {
+    let mut chars = ops.chars().peekable();
+    let mut out = Vec::new();
+    while let Some(c) = chars.next() {
+        out.push(match c {
+            'b' => Conversion::To(standard(2)?),
+            'o' => Conversion::To(standard(8)?),
+            'h' => Conversion::To(standard(16)?),
+            'B' => Conversion::From(standard(2)?),
+            'O' => Conversion::From(standard(8)?),
+            'H' => Conversion::From(standard(16)?),
+            '0'..='9' => Conversion::To(standard(
+                read_base(c.to_string(), &mut chars)?.unwrap_or_default(),
+            )?),
+            'f' => Conversion::From(standard(require_base(&mut chars, c)?)?),
+            'd' => Conversion::ToDigits(digit_base(&mut chars)?),
+            'D' => Conversion::FromDigits(digit_base(&mut chars)?),
+            'j' => Conversion::ToBijective(require_base(&mut chars, c)?),
+            'J' => Conversion::FromBijective(require_base(&mut chars, c)?),
+            'a' => Conversion::To(alphabet(&chars.by_ref().collect::<String>())?),
+            'A' => Conversion::From(alphabet(&chars.by_ref().collect::<String>())?),
+            _ => {
+                return Err(ArnError::Type(format!(
+                    "unrecognized base conversion character `{c}`"
+                )))
+            }
+        });
+    }
+    Ok(out)
+}
+
+impl Conversion {
+    // Whether this starts from a single string of digits
+    #[inline]
+    pub fn reads_number(&self) -> bool {
+        matches!(self, Self::From(_) | Self::FromBijective(_))
+    }
+
+    // Converts <cur>, or every value in it if it is a sequence
+    pub fn apply(&self, cur: Dynamic) -> ArnResult<Dynamic> {
+        if let Self::FromDigits(base) = self {
+            return join_digits(cur, *base);
+        }
+
+        if cur.is_array() {
+            let values = cur
+                .literal_array()
+                .map(|val| self.apply(val?))
+                .collect::<ArnResult<Vec<_>>>()?;
+            // Strings of digits are padded to the same length
+            return Ok(match self {
+                Self::To(digits) => {
+                    let values: Vec<String> =
+                        values.into_iter().map(Dynamic::literal_string).collect();
+                    let max = values.iter().map(|v| v.chars().count()).max().unwrap_or(0);
+                    Dynamic::from(
+                        values
+                            .into_iter()
+                            .map(|v| {
+                                let pad = max - v.chars().count();
+                                std::iter::repeat_n(digits[0], pad)
+                                    .chain(v.chars())
+                                    .collect()
+                            })
+                            .collect::<Vec<String>>(),
+                    )
+                }
+                _ => Dynamic::from(values),
+            });
+        }
+
+        let cur = cur.literal_string();
+        Ok(match self {
+            Self::To(digits) => Dynamic::from(to_alphabet(&parse_int(&cur)?, digits)),
+            Self::From(digits) => Dynamic::from(from_alphabet(cur.trim(), digits)?),
+            Self::ToDigits(base) => Dynamic::from(to_digits(&parse_int(&cur)?, *base)),
+            Self::ToBijective(base) => Dynamic::from(to_bijective(&parse_int(&cur)?, *base)?),
+            Self::FromBijective(base) => Dynamic::from(from_bijective(cur.trim(), *base)?),
+            Self::FromDigits(_) => unreachable!(),
+        })
+    }
+}
+
+// A list of digits becomes one number, a list of lists becomes a list of numbers
+fn join_digits(cur: Dynamic, base: u32) -> ArnResult<Dynamic> {
+    let values = cur.literal_array().collect::<ArnResult<Vec<_>>>()?;
+    if !values.is_empty() && values.iter().all(Dynamic::is_array) {
+        return values
+            .into_iter()
+            .map(|val| join_digits(val, base))
+            .collect::<ArnResult<Vec<_>>>()
+            .map(Dynamic::from);
+    }
+
+    let digits = values
+        .into_iter()
+        .map(|val| {
+            let val = val.literal_string();
+            let digit = parse_int(&val)?;
+            if Int::from(digit.abs_ref()) < base {
+                Ok(digit)
+            } else {
+                Err(ArnError::Type(format!(
+                    "`{val}` is not a digit in base {base}"
+                )))
+            }
+        })
+        .collect::<ArnResult<Vec<_>>>()?;
+    Ok(Dynamic::from(from_digits(&digits, base)))
+}
@@ -0,0 +1,282 @@
+// Counting and listing the ways to arrange the values of finite sequences
+
+use super::error::{ArnError, ArnResult};
+use super::num::Int;
+use super::types::{Dynamic, Lazy, Sequence};
+
+// The number of partitions stops fitting in 64 bits at 417
+const PARTITION_LIMIT: u32 = 416;
+
+// <k> as a size to pick, negative sizes pick nothing
+fn pick_size(k: &Int, op: &str) -> ArnResult<Option<u32>> {
+    if *k < 0 {
+        return Ok(None);
+    }
+    k.to_u32()
+        .map(Some)
+        .ok_or_else(|| ArnError::Type(format!("`{op}` cannot pick `{k}` values")))
+}
+
+// The number of ways to pick <k> of <n> values, nCr
+pub fn choose(n: &Int, k: &Int, op: &str) -> ArnResult<Int> {
+    Ok(pick_size(k, op)?.map_or_else(Int::new, |k| Int::from(n.binomial_ref(k))))
+}
+
+// The number of ways to pick <k> of <n> values in order, nPr
+pub fn arrange(n: &Int, k: &Int, op: &str) -> ArnResult<Int> {
+    Ok(pick_size(k, op)?.map_or_else(Int::new, |k| {
+        Int::from(n.binomial_ref(k)) * Int::from(Int::factorial(k))
+    }))
+}
+
+// <n>!, the empty product 1 for anything below 1
+pub fn factorial(n: &Int, op: &str) -> ArnResult<Int> {
+    if *n < 1 {
+        return Ok(Int::from(1));
+    }
+    n.to_u32()
+        .map(|n| Int::from(Int::factorial(n)))
+        .ok_or_else(|| ArnError::Type(format!("`{op}` cannot take the factorial of `{n}`")))
+}
+
+// The number of ways to write <n> as a sum of positive integers, with Euler's pentagonal numbers
+pub fn partitions(n: u32) -> Int {
+    let n = n as usize;
+    let mut counts = vec![Int::from(1)];
+    for m in 1..=n {
+        let mut total = Int::new();
+        for k in 1.. {
+            let pentagonal = k * (3 * k - 1) / 2;
+            if pentagonal > m {
+                break;
+            }
+            let mut term = Int::from(&counts[m - pentagonal]);
+            if pentagonal + k <= m {
+                term += &counts[m - pentagonal - k];
+            }
+            if k % 2 == 1 {
+                total += term;
+            } else {
+                total -= term;
+            }
+        }
+        counts.push(total);
+    }
+    counts.swap_remove(n)
+}
+
+// What is being listed
+#[derive(Clone, Debug)]
+pub enum Arrangement {
+    /// Every ordering of the given number of values
+    Permutations(Vec<Dynamic>, usize),
+    /// Every choice of the given number of values, keeping their order
+    Combinations(Vec<Dynamic>, usize),
+    /// One value from each list, in every way
+    Product(Vec<Vec<Dynamic>>),
+    /// Every subset of the values, keeping their order, smallest first
+    Subsets(Vec<Dynamic>),
+    /// Sums of positive integers equal to the number, largest parts first
+    Partitions(u32),
+}
+
+// The arrangements of values one at a time, in lexicographic order of their indices
+// (subsets of the same size are in the order counting up in binary reaches them instead)
+#[derive(Clone, Debug)]
+pub struct Arrangements {
+    kind: Arrangement,
+    // Indices of the values in the last arrangement (or the parts of a partition), None before the first
+    indices: Option<Vec<usize>>,
+    // How far each position of a permutation is from cycling back
+    cycles: Vec<usize>,
+    done: bool,
+}
+
+impl Arrangements {
+    pub fn new(kind: Arrangement) -> Self {
+        Self {
+            kind,
+            indices: None,
+            cycles: Vec::new(),
+            done: false,
+        }
+    }
+
+    // How many arrangements there are, None if there are too many to count
+    pub fn count(&self) -> Option<usize> {
+        let count = match &self.kind {
+            Arrangement::Permutations(values, k) => {
+                arrange(&Int::from(values.len()), &Int::from(*k), "").ok()?
+            }
+            Arrangement::Combinations(values, k) => {
+                choose(&Int::from(values.len()), &Int::from(*k), "").ok()?
+            }
+            Arrangement::Product(lists) => lists
+                .iter()
+                .fold(Int::from(1), |acc, list| acc * list.len()),
+            Arrangement::Subsets(values) => Int::from(1) << values.len() as u32,
+            Arrangement::Partitions(n) if *n <= PARTITION_LIMIT => partitions(*n),
+            Arrangement::Partitions(_) => return None,
+        };
+        count.to_usize()
+    }
+
+    // The first arrangement, None if there are none
+    fn first(&mut self) -> Option<Vec<usize>> {
+        match &self.kind {
+            Arrangement::Permutations(values, k) => {
+                let n = values.len();
+                if *k > n {
+                    return None;
+                }
+                self.cycles = (n - k + 1..=n).rev().collect();
+                Some((0..n).collect())
+            }
+            Arrangement::Combinations(values, k) => (*k <= values.len()).then(|| (0..*k).collect()),
+            Arrangement::Product(lists) => {
+                (!lists.iter().any(Vec::is_empty)).then(|| vec![0; lists.len()])
+            }
+            Arrangement::Subsets(_) | Arrangement::Partitions(0) => Some(Vec::new()),
+            Arrangement::Partitions(n) => Some(vec![*n as usize]),
+        }
+    }
+
+    // Moves <indices> on to the next arrangement, false once they were the last
+    fn advance(&mut self, indices: &mut Vec<usize>) -> bool {
+        match &self.kind {
+            Arrangement::Permutations(_, k) => {
+                let n = indices.len();
+                for i in (0..*k).rev() {
+                    self.cycles[i] -= 1;
+                    if self.cycles[i] == 0 {
+                        indices[i..].rotate_left(1);
+                        self.cycles[i] = n - i;
+                    } else {
+                        indices.swap(i, n - self.cycles[i]);
+                        return true;
+                    }
+                }
+                false
+            }
+
+            Arrangement::Combinations(values, k) => {
+                let n = values.len();
+                match (0..*k).rev().find(|&i| indices[i] != i + n - k) {
+                    Some(i) => {
+                        indices[i] += 1;
+                        for j in i + 1..*k {
+                            indices[j] = indices[j - 1] + 1;
+                        }
+                        true
+                    }
+                    None => false,
+                }
+            }
+
+            Arrangement::Product(lists) => {
+                for (i, list) in lists.iter().enumerate().rev() {
+                    indices[i] += 1;
+                    if indices[i] < list.len() {
+                        return true;
+                    }
+                    indices[i] = 0;
+                }
+                false
+            }
+
+            Arrangement::Subsets(values) => {
+                // Raise the first index that can move without reaching the next one, resetting those
+                // before it, or start on the subsets one value larger
+                let n = values.len();
+                let k = indices.len();
+                match (0..k).find(|&i| indices[i] + 1 < indices.get(i + 1).copied().unwrap_or(n)) {
+                    Some(i) => {
+                        indices[i] += 1;
+                        for (j, index) in indices[..i].iter_mut().enumerate() {
+                            *index = j;
+                        }
+                    }
+                    None if k < n => *indices = (0..=k).collect(),
+                    None => return false,
+                }
+                true
+            }
+
+            Arrangement::Partitions(_) => {
+                // Lower the last part above 1, then split what is left into parts no larger
+                let Some(pos) = indices.iter().rposition(|&part| part > 1) else {
+                    return false;
+                };
+                let mut rest = indices.len() - pos;
+                indices.truncate(pos + 1);
+                indices[pos] -= 1;
+                let size = indices[pos];
+                while rest > 0 {
+                    let part = rest.min(size);
+                    indices.push(part);
+                    rest -= part;
+                }
+                true
+            }
+        }
+    }
+
+    fn term(&self, indices: &[usize]) -> Dynamic {
+        let pick = |values: &[Dynamic], indices: &[usize]| {
+            Dynamic::from(
+                indices
+                    .iter()
+                    .map(|&i| values[i].clone())
+                    .collect::<Vec<_>>(),
+            )
+        };
+        match &self.kind {
+            Arrangement::Permutations(values, k) => pick(values, &indices[..*k]),
+            Arrangement::Combinations(values, _) | Arrangement::Subsets(values) => {
+                pick(values, indices)
+            }
+            Arrangement::Product(lists) => Dynamic::from(
+                lists
+                    .iter()
+                    .zip(indices)
+                    .map(|(list, &i)| list[i].clone())
+                    .collect::<Vec<_>>(),
+            ),
+            Arrangement::Partitions(_) => Dynamic::from(
+                indices
+                    .iter()
+                    .map(|&part| Int::from(part))
+                    .collect::<Vec<_>>(),
+            ),
+        }
+    }
+
+    pub fn next_term(&mut self) -> Option<Dynamic> {
+        if self.done {
+            return None;
+        }
+        let indices = match self.indices.take() {
+            None => self.first(),
+            Some(mut indices) => self.advance(&mut indices).then_some(indices),
+        };
+        if let Some(indices) = indices {
+            let term = self.term(&indices);
+            self.indices = Some(indices);
+            Some(term)
+        } else {
+            self.done = true;
+            None
+        }
+    }
+}
+
+// A sequence that lists the arrangements as they are needed
+pub fn sequence(kind: Arrangement) -> Dynamic {
+    let arrangements = Arrangements::new(kind);
+    let length = arrangements.count();
+    let lazy = Lazy::Arrange(Box::new(arrangements));
+    Dynamic::from(match length {
+        Some(length) => Sequence::lazy_with_length(lazy, length),
+        None => Sequence::lazy_uncounted(lazy),
+    })
+}
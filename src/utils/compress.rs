@@ -1,15 +1,23 @@
 use super::{consts::CODEPAGE, num::Num};
 
-pub fn pack(code: &str) -> String {
-    let code = code.replace('\n', "\\n").chars().collect::<Vec<_>>();
-    let bytes = code.iter().map(|r| (*r as u8 as i32 - 32) as u8);
+// Only printable ASCII (and newlines) can be packed, anything else gives `None`
+pub fn pack(code: &str) -> Option<String> {
+    let bytes = code
+        .replace('\n', "\\n")
+        .chars()
+        .map(|r| match r {
+            ' '..='~' => Some(r as u8 - 32),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
 
-    let bytes = pack_bytes(bytes);
-    return bytes
-        .iter()
-        .map(|r| CODEPAGE[*r as usize].to_string())
-        .collect::<Vec<_>>()
-        .join("");
+    let bytes = pack_bytes(bytes.into_iter());
+    Some(
+        bytes
+            .iter()
+            .map(|r| CODEPAGE[*r as usize].to_string())
+            .collect::<String>(),
+    )
 }
 
 #[inline]
@@ -42,8 +50,7 @@ pub fn unpack(packed: &str) -> String {
     bytes
         .iter()
         .map(|r| String::from_utf8(vec![*r + 32]).unwrap())
-        .collect::<Vec<_>>()
-        .join("")
+        .collect::<String>()
         .replace("\\n", "\n")
 }
 
@@ -67,7 +74,28 @@ where
     result
 }
 
+// Code that can't be packed may still be plain Arn with non-ASCII text in its strings
 #[inline]
 pub fn is_packed(code: &str) -> bool {
-    unpack(&pack(code)) != code
+    match pack(code) {
+        Some(packed) => unpack(&packed) != code,
+        None => !only_in_strings(code) && code.chars().all(|c| CODEPAGE.contains(&c)),
+    }
+}
+
+// Whether every char outside of printable ASCII is inside a `"..."` string
+fn only_in_strings(code: &str) -> bool {
+    let mut in_string = false;
+    let mut last = ' ';
+    for c in code.chars() {
+        match c {
+            '"' if !in_string || last != '\\' => in_string = !in_string,
+            ' '..='~' | '\n' => {}
+            _ if !in_string => return false,
+            _ => {}
+        }
+        last = c;
+    }
+
+    !in_string
 }
@@ -29,6 +29,12 @@ macro_rules! operators {
                 }
             }
         }
+
+        impl Default for Operators {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
     };
 }
 
@@ -72,20 +78,20 @@ macro_rules! hashmap {
 // First number is precedence, second is left # of args, third is right # of args
 operators! {
     '.': 11; 1-1,
-    '^': 10; 1-1, "<>": 10; 1-1,
-    '*': 9; 1-1, '/': 9; 1-1,
+    '^': 10; 1-1, "<>": 10; 1-1, ".^": 10; 1-1,
+    '*': 9; 1-1, '/': 9; 1-1, ".*": 9; 1-1, ":.": 9; 1-1,
     '%': 8; 1-1,
     ":|": 7; 1-1, ":!": 7; 1-1,
-    '+': 6; 1-1, '-': 6; 1-1, ".$": 6; 1-1,
-    ".~": 5; 1-0, "=>": 5; 1-1, "->": 5; 1-1, '~': 5; 0-1, '#': 5; 1-0, ';': 5; 1-1, ":_": 5; 1-0, ":%": 5; 1-0, ".|": 5; 1-0, ".<": 5; 1-0, "..": 5; 1-0, ".=": 5; 1-0,
-    ":n": 4; 1-0, ":s": 4; 1-0, ":}": 4; 1-0, ":{": 4; 1-0, ".}": 4; 1-0, ".{": 4; 1-0, ":@": 4; 1-0, "^*": 4; 1-0, "&.": 4; 0-3, ":i": 4; 1-1,
+    '+': 6; 1-1, '-': 6; 1-1, ".$": 6; 1-1, ".+": 6; 1-1,
+    ".~": 5; 1-0, "=>": 5; 1-1, "->": 5; 1-1, '~': 5; 0-1, '#': 5; 1-0, ';': 5; 1-1, ":_": 5; 1-0, ":%": 5; 1-0, ".|": 5; 1-0, ":R": 5; 1-0, ":I": 5; 1-0, ":C": 5; 1-0, ":A": 5; 1-0, ".<": 5; 1-0, "..": 5; 1-0, ".=": 5; 1-0,
+    ":n": 4; 1-0, ":s": 4; 1-0, ":}": 4; 1-0, ":{": 4; 1-0, ".}": 4; 1-0, ".{": 4; 1-0, ":@": 4; 1-0, ":c": 4; 1-0, ":k": 4; 1-0, ":V": 4; 1-0, "^*": 4; 1-0, "&.": 4; 0-3, "^%": 4; 0-3, ":i": 4; 1-1, ":p": 4; 1-1, ":P": 4; 1-1, ":B": 4; 1-1,
     '!': 4; 0-1, ":v": 4; 0-1, ":^": 4; 0-1, "++": 4; 0-1, "--": 4; 0-1, ":*": 4; 0-1, ":/": 4; 0-1,
     ":+": 4; 0-1, ":-": 4; 0-1, ":>": 4; 0-1, ":<": 4; 0-1, "|:": 4; 0-1, "?.": 4; 0-1, "#.": 4; 0-1, "*.": 4; 0-1,
-    "$.": 4; 0-1, 'z': 4; 1-1, "#>": 4; 0-1, "#:": 4; 0-1, '?': 4; 1-1, "!.": 4; 0-1,
+    "$.": 4; 0-1, 'z': 4; 1-1, "#>": 4; 0-1, "#:": 4; 0-1, '?': 4; 1-1, "?<": 4; 1-1, "?>": 4; 1-1, "?%": 4; 1-1, ":?": 4; 1-1, ":#": 4; 1-1, "!.": 4; 0-1,
     '|': 3; 1-1,
     '=': 2; 1-1, "!=": 2; 1-1, '<': 2; 1-1, "<=": 2; 1-1, '>': 2; 1-1, ">=": 2; 1-1,
     "&&": 1; 1-1, "||": 1; 1-1,
-    ':': 0; 1-1, "::": 0; 1-1, "??": 0; 1-2, '@': 0; 1-1, '&': 0; 1-1, '$': 0; 0-2, "$:": 0; 0-2, "/:": 0; 0-2, "\\": 0; 1-1, ":\\": 0; 1-1,
+    ':': 0; 1-1, "::": 0; 1-1, "??": 0; 1-2, '@': 0; 1-1, ":g": 0; 1-1, '&': 0; 1-1, '$': 0; 0-2, "$:": 0; 0-2, ":$": 0; 0-2, "/:": 0; 0-2, "\\": 0; 1-1, ":\\": 0; 1-1,
     ":=": -1; 1-1
 }
 
@@ -1,20 +1,31 @@
 // This file was ported directly from js, so excuse the mess
 
+use std::collections::HashMap;
+
 use super::consts::COMPRESSED_CHARS;
+use super::error::{ArnError, ArnResult};
+
+lazy_static! {
+    static ref DICTIONARY: Vec<&'static str> = include_str!("../../dictionary.txt")
+        .split('\n')
+        .filter(|n| !n.is_empty())
+        .collect();
+}
 
 fn capitalize(word: &str, existing: bool) -> String {
     if existing {
         word.to_owned()
     } else {
-        word.chars().next().unwrap().to_uppercase().to_string() + &word[1..]
+        let mut chars = word.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
     }
 }
 
 pub fn decompress(chars: &str, all_cap: bool) -> String {
-    let dictionary = include_str!("../../dictionary.txt")
-        .split("\n")
-        .filter(|n| !n.is_empty())
-        .collect::<Vec<_>>();
+    let dictionary = &*DICTIONARY;
     let s = chars.trim().chars().collect::<Vec<char>>();
 
     let mut decomp = String::new();
@@ -32,23 +43,130 @@ pub fn decompress(chars: &str, all_cap: bool) -> String {
                     !all_cap && !decomp.is_empty(),
                 ));
             } else {
-                decomp.push_str(&format!(
-                    "{}{}",
-                    capitalize(
-                        dictionary
-                            [COMPRESSED_CHARS.iter().position(|c| *c == first).unwrap() * 100]
-                            .trim(),
-                        !all_cap && !decomp.is_empty()
-                    ),
-                    second
+                decomp.push_str(&capitalize(
+                    dictionary[COMPRESSED_CHARS.iter().position(|c| *c == first).unwrap() * 100]
+                        .trim(),
+                    !all_cap && !decomp.is_empty(),
                 ));
+                decomp.push(second);
             }
             chr += 2;
         } else {
             chr += 1;
-            decomp.push_str(&first.to_string());
+            decomp.push(first);
         }
     }
 
     decomp
 }
+
+// How a single dictionary word can be written
+#[derive(Default)]
+struct Encoding {
+    // Two compressed chars
+    pair: Option<String>,
+    // One compressed char, only usable when a literal char follows
+    single: Option<char>,
+}
+
+// Maps every word (as it would be decompressed) to its encoding, avoiding <delim>
+fn encodings(delim: char, capitalized: bool) -> HashMap<String, Encoding> {
+    let mut map: HashMap<String, Encoding> = HashMap::new();
+    let len = COMPRESSED_CHARS.len();
+
+    for (i, word) in DICTIONARY.iter().enumerate() {
+        let (first, second) = (i / 100, i % 100);
+        if first >= len || second >= len || COMPRESSED_CHARS[first] == delim {
+            continue;
+        }
+
+        let word = capitalize(word.trim(), !capitalized);
+        let entry = map.entry(word).or_default();
+        if COMPRESSED_CHARS[second] != delim && entry.pair.is_none() {
+            entry.pair = Some(
+                [COMPRESSED_CHARS[first], COMPRESSED_CHARS[second]]
+                    .iter()
+                    .collect(),
+            );
+        }
+        if second == 0 {
+            entry.single = Some(COMPRESSED_CHARS[first]);
+        }
+    }
+
+    map
+}
+
+// Shortest encoding of <text> between <delim>s, if there is one
+fn compress_with(text: &str, delim: char) -> Option<String> {
+    // `decompress` trims its input, so the ends can't be whitespace
+    if text.trim() != text {
+        return None;
+    }
+
+    let all_cap = delim == '\'';
+    let words = encodings(delim, all_cap);
+    // Only the very first word is capitalized with a backtick
+    let first_words = if all_cap {
+        None
+    } else {
+        Some(encodings(delim, true))
+    };
+    let longest = DICTIONARY.iter().map(|w| w.trim().len()).max().unwrap_or(0);
+    let is_literal = |c: char| !COMPRESSED_CHARS.contains(&c) && c != '\u{2192}';
+
+    let text = text.chars().collect::<Vec<_>>();
+    let n = text.len();
+    // best[i] holds the shortest encoding of text[i..]
+    let mut best: Vec<Option<String>> = vec![None; n + 1];
+    best[n] = Some(String::new());
+
+    for i in (0..n).rev() {
+        let mut candidates = Vec::new();
+
+        if is_literal(text[i]) {
+            if let Some(rest) = &best[i + 1] {
+                candidates.push(format!("{}{rest}", text[i]));
+            }
+        }
+
+        let lookup = match &first_words {
+            Some(first_words) if i == 0 => first_words,
+            _ => &words,
+        };
+        for end in i + 1..=n.min(i + longest) {
+            let word = text[i..end].iter().collect::<String>();
+            let Some(encoding) = lookup.get(&word) else {
+                continue;
+            };
+
+            if let (Some(pair), Some(rest)) = (&encoding.pair, &best[end]) {
+                candidates.push(format!("{pair}{rest}"));
+            }
+            if let Some(single) = encoding.single {
+                if end < n && is_literal(text[end]) {
+                    if let Some(rest) = &best[end + 1] {
+                        candidates.push(format!("{single}{}{rest}", text[end]));
+                    }
+                }
+            }
+        }
+
+        best[i] = candidates.into_iter().min_by_key(|c| c.chars().count());
+    }
+
+    best[0].take().map(|s| format!("{delim}{s}{delim}"))
+}
+
+// Finds the shortest compressed string literal (`` `...` `` or `'...'`) that decompresses to <text>
+pub fn compress(text: &str) -> ArnResult<String> {
+    ['`', '\'']
+        .iter()
+        .filter_map(|delim| compress_with(text, *delim))
+        .min_by_key(|s| s.chars().count())
+        .ok_or_else(|| {
+            ArnError::Input(format!(
+                "`{text}` cannot be written with words from the dictionary"
+            ))
+        })
+}
@@ -2,11 +2,15 @@ use std::fmt;
 use std::rc::Rc;
 use std::{cell::RefCell, collections::HashMap};
 
+use super::error::{ArnError, ArnResult};
 use super::types::{Dynamic, Env};
 
+// Signature shared by every value and function stored in an `Environment`
+pub type Func = dyn Fn(Env, Dynamic) -> ArnResult<Dynamic>;
+
 #[derive(Clone)]
 pub struct Environment {
-    pub vals: HashMap<String, Rc<dyn Fn(Env, Dynamic) -> Dynamic>>,
+    pub vals: HashMap<String, Rc<Func>>,
 }
 
 impl Environment {
@@ -16,12 +20,12 @@ impl Environment {
         }
     }
 
-    pub fn define<T: 'static, const SIZE: usize>(&mut self, names: [&str; SIZE], f: T)
+    pub fn define<T, const SIZE: usize>(&mut self, names: [&str; SIZE], f: T)
     where
-        T: Fn(Env, Dynamic) -> Dynamic,
+        T: 'static + Fn(Env, Dynamic) -> ArnResult<Dynamic>,
     {
-        let ptr: Rc<dyn Fn(Env, Dynamic) -> Dynamic> = Rc::new(f);
-        for name in std::array::IntoIter::new(names) {
+        let ptr: Rc<Func> = Rc::new(f);
+        for name in names {
             self.vals.insert(name.trim().to_owned(), Rc::clone(&ptr));
         }
     }
@@ -32,12 +36,12 @@ impl Environment {
     {
         self.vals.insert(
             name.trim().to_owned(),
-            Rc::new(move |_, _| Dynamic::from(val.clone())),
+            Rc::new(move |_, _| Ok(Dynamic::from(val.clone()))),
         );
     }
 
     #[inline]
-    pub fn get_var(&self, name: &str) -> Dynamic {
+    pub fn get_var(&self, name: &str) -> ArnResult<Dynamic> {
         // Dummy call, assumes it is a constant value
         self.attempt_call(
             name,
@@ -46,11 +50,11 @@ impl Environment {
         )
     }
 
-    pub fn attempt_call(&self, name: &str, env: &Env, arg: Dynamic) -> Dynamic {
+    pub fn attempt_call(&self, name: &str, env: &Env, arg: Dynamic) -> ArnResult<Dynamic> {
         let f = self
             .vals
             .get(name)
-            .unwrap_or_else(|| panic!("Unrecognized value {}", name));
+            .ok_or_else(|| ArnError::UnknownVariable(name.to_owned()))?;
         f(Rc::clone(env), arg)
     }
 }
@@ -0,0 +1,147 @@
+use std::fmt::Write;
+use std::fmt::{self, Display, Formatter};
+
+use super::tokens::Span;
+
+// Shorthand used by anything that evaluates Arn code
+pub type ArnResult<T> = Result<T, ArnError>;
+
+// Everything that can go wrong while lexing or evaluating a program
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArnError {
+    /// The program text could not be tokenized
+    Lex(String),
+
+    /// A name was used that has never been defined
+    UnknownVariable(String),
+
+    /// A value could not be used the way an operator needed it
+    Type(String),
+
+    /// An operation that needs a finite sequence was given an infinite one
+    InfiniteSequence(String),
+
+    /// An index was outside of the bounds of a sequence
+    IndexOutOfRange(String),
+
+    /// An operator that the interpreter does not know about
+    UnknownOperator(String),
+
+    /// Bad input given to the interpreter itself (flags, STDIN)
+    Input(String),
+
+    /// Reading the input or writing the output failed
+    Io(String),
+
+    /// Any of the above, along with the part of the program that caused it
+    Spanned(Box<ArnError>, Span),
+}
+
+impl ArnError {
+    #[inline]
+    pub fn infinite(op: &str) -> Self {
+        Self::InfiniteSequence(format!("`{op}` cannot be used on an infinite sequence"))
+    }
+
+    // A finite sequence with more terms than fit in a usize
+    #[inline]
+    pub fn uncounted(op: &str) -> Self {
+        Self::Type(format!(
+            "`{op}` cannot be used on a sequence with too many terms to count"
+        ))
+    }
+
+    #[inline]
+    pub fn empty(op: &str) -> Self {
+        Self::IndexOutOfRange(format!("`{op}` cannot be used on an empty sequence"))
+    }
+
+    // Attaches <span> unless a more specific one is already present
+    pub fn with_span(self, span: Span) -> Self {
+        match self {
+            Self::Spanned(..) => self,
+            _ if span.is_empty() => self,
+            _ => Self::Spanned(Box::new(self), span),
+        }
+    }
+
+    // Drops the span, used when the failing code was generated by the interpreter
+    pub fn without_span(self) -> Self {
+        match self {
+            Self::Spanned(inner, _) => *inner,
+            _ => self,
+        }
+    }
+
+    #[inline]
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Self::Spanned(_, span) => Some(*span),
+            _ => None,
+        }
+    }
+
+    // Moves the span back by <offset> chars, dropping it if it leaves the first <len> chars
+    pub fn shift_span(self, offset: usize, len: usize) -> Self {
+        match self {
+            Self::Spanned(inner, span) if span.start >= offset && span.end <= offset + len => {
+                Self::Spanned(inner, Span::new(span.start - offset, span.end - offset))
+            }
+            _ => self.without_span(),
+        }
+    }
+
+    // Formats the error, underlining the part of <source> it came from
+    pub fn render(&self, source: &str) -> String {
+        let mut out = self.to_string();
+        let Some(span) = self.span() else {
+            return out;
+        };
+
+        // Find the line that contains the start of the span
+        let mut line_start = 0;
+        for (number, line) in source.split('\n').enumerate() {
+            let line_len = line.chars().count();
+            if span.start <= line_start + line_len {
+                let col = span.start - line_start;
+                let width = span
+                    .end
+                    .min(line_start + line_len)
+                    .saturating_sub(span.start);
+                let number = (number + 1).to_string();
+
+                write!(out, "\n{number} | {line}").unwrap();
+                write!(
+                    out,
+                    "\n{} | {}{}",
+                    " ".repeat(number.len()),
+                    " ".repeat(col),
+                    "^".repeat(width.max(1))
+                )
+                .unwrap();
+                break;
+            }
+            line_start += line_len + 1;
+        }
+
+        out
+    }
+}
+
+impl Display for ArnError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lex(msg) => write!(f, "Syntax error: {msg}"),
+            Self::UnknownVariable(name) => write!(f, "Unrecognized value `{name}`"),
+            Self::Type(msg) => write!(f, "Type error: {msg}"),
+            Self::InfiniteSequence(msg) => write!(f, "Infinite sequence: {msg}"),
+            Self::IndexOutOfRange(msg) => write!(f, "Index out of range: {msg}"),
+            Self::UnknownOperator(op) => write!(f, "Unrecognized operator `{op}`"),
+            Self::Input(msg) => write!(f, "Invalid input: {msg}"),
+            Self::Io(msg) => write!(f, "I/O error: {msg}"),
+            Self::Spanned(inner, _) => write!(f, "{inner}"),
+        }
+    }
+}
+
+impl std::error::Error for ArnError {}
@@ -0,0 +1,97 @@
+use std::cell::RefCell;
+use std::io::{self, Read, Write};
+use std::rc::Rc;
+
+use super::error::{ArnError, ArnResult};
+use super::types::Dynamic;
+
+// Where the text bound to `_` comes from
+pub trait Input {
+    fn read_input(&mut self) -> io::Result<String>;
+}
+
+impl Input for String {
+    fn read_input(&mut self) -> io::Result<String> {
+        Ok(self.clone())
+    }
+}
+
+impl Input for &str {
+    fn read_input(&mut self) -> io::Result<String> {
+        Ok((*self).to_owned())
+    }
+}
+
+impl<T: Input + ?Sized> Input for Box<T> {
+    fn read_input(&mut self) -> io::Result<String> {
+        self.as_mut().read_input()
+    }
+}
+
+// The process' STDIN, treated as empty when nothing is piped in
+pub struct StdinInput;
+
+impl Input for StdinInput {
+    fn read_input(&mut self) -> io::Result<String> {
+        if atty::is(atty::Stream::Stdin) {
+            return Ok(String::new());
+        }
+
+        let mut buffer = String::new();
+        io::stdin().read_to_string(&mut buffer)?;
+        Ok(buffer.trim_end_matches('\n').to_owned())
+    }
+}
+
+// Where a program writes, shared between the interpreter and the `o`/`ol` builtins
+pub type Output = Rc<RefCell<dyn Write>>;
+
+pub fn write(output: &Output, text: &str) -> ArnResult<()> {
+    let mut output = output.borrow_mut();
+    output
+        .write_all(text.as_bytes())
+        .and_then(|()| output.flush())
+        .map_err(|e| ArnError::Io(e.to_string()))
+}
+
+// Writes <value> and then <end>
+// Infinite sequences are written a term at a time as they are computed, stopping at the first error
+pub fn write_value(output: &Output, value: &Dynamic, end: &str) -> ArnResult<()> {
+    if value.is_array() {
+        let seq = value.clone().literal_array();
+        if !seq.is_finite() {
+            for entry in seq {
+                write(output, &format!("{}\n", entry?.entry_string()))?;
+            }
+            return write(output, end);
+        }
+    }
+
+    write(output, &format!("{value}{end}"))
+}
+
+// In-memory output that can be read back after (or while) a program runs
+// Clones share the same buffer
+#[derive(Clone, Default)]
+pub struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl SharedBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.borrow()).into_owned()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,248 @@
+// Linear algebra over nested sequences of numbers
+
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use super::error::{ArnError, ArnResult};
+use super::num::{is_arn_num, Cpx, Int, Num};
+use super::types::{Dynamic, Env};
+
+// Rows of equal length
+pub type Matrix = Vec<Vec<Dynamic>>;
+
+#[inline]
+fn zero() -> Dynamic {
+    Dynamic::from(Int::from(0))
+}
+
+#[inline]
+fn is_zero(n: &Dynamic) -> bool {
+    n.cmp_num(&zero()) == Some(Ordering::Equal)
+}
+
+// Distance from 0, used to pick the largest pivot
+fn magnitude(n: &Dynamic) -> Dynamic {
+    n.mutate_complex(Cpx::abs)
+        .unwrap_or_else(|| n.mutate_exact(|n| Some(n.abs()), Num::abs))
+}
+
+#[inline]
+fn size(m: &Matrix) -> String {
+    format!("{}x{}", m.len(), m.first().map_or(0, Vec::len))
+}
+
+// Collects every value of <seq>, which has to be finite
+pub fn to_vector(env: &Env, seq: Dynamic, op: &str) -> ArnResult<Vec<Dynamic>> {
+    let seq = seq.literal_array().set_env_self(Rc::clone(env));
+    if !seq.is_finite() {
+        return Err(ArnError::infinite(op));
+    }
+    seq.collect()
+}
+
+// <n> as a number, booleans are 0 or 1 and strings such as those read from STDIN are parsed
+fn to_entry(n: Dynamic, row: usize, op: &str) -> ArnResult<Dynamic> {
+    if n.is_num() {
+        return Ok(n);
+    }
+    if n.is_bool() || n.is_string() && is_arn_num(&n.clone().literal_string()) {
+        return Ok(n.into_num());
+    }
+    Err(ArnError::Type(format!(
+        "`{op}` needs a matrix of numbers, but row {row} contains `{n}`"
+    )))
+}
+
+// Reads <seq> as a list of rows, which must all be sequences of numbers of the same length
+pub fn to_matrix(env: &Env, seq: Dynamic, op: &str) -> ArnResult<Matrix> {
+    let rows = to_vector(env, seq, op)?
+        .into_iter()
+        .enumerate()
+        .map(|(i, row)| {
+            if row.is_array() {
+                to_vector(env, row, op)?
+                    .into_iter()
+                    .map(|n| to_entry(n, i + 1, op))
+                    .collect()
+            } else {
+                Err(ArnError::Type(format!(
+                    "`{op}` needs a matrix, but row {} is `{row}` instead of a sequence",
+                    i + 1
+                )))
+            }
+        })
+        .collect::<ArnResult<Matrix>>()?;
+
+    if let Some(first) = rows.first() {
+        if let Some(i) = rows.iter().position(|row| row.len() != first.len()) {
+            return Err(ArnError::Type(format!(
+                "`{op}` cannot be used on a ragged matrix, row {} has length {} instead of {}",
+                i + 1,
+                rows[i].len(),
+                first.len()
+            )));
+        }
+    }
+    Ok(rows)
+}
+
+fn require_square(m: &Matrix, op: &str) -> ArnResult<()> {
+    if m.iter().all(|row| row.len() == m.len()) {
+        Ok(())
+    } else {
+        Err(ArnError::Type(format!(
+            "`{op}` needs a square matrix, not a {} one",
+            size(m)
+        )))
+    }
+}
+
+// Sum of the products of the values in <left> and <right>
+pub fn dot(left: &[Dynamic], right: &[Dynamic], op: &str) -> ArnResult<Dynamic> {
+    if left.len() != right.len() {
+        return Err(ArnError::Type(format!(
+            "`{op}` needs vectors of the same length, not {} and {}",
+            left.len(),
+            right.len()
+        )));
+    }
+    Ok(left.iter().zip(right).fold(zero(), |acc, (l, r)| {
+        acc.arithmetic('+', &l.arithmetic('*', r))
+    }))
+}
+
+pub fn multiply(left: &Matrix, right: &Matrix, op: &str) -> ArnResult<Matrix> {
+    let inner = left.first().map_or(0, Vec::len);
+    if inner != right.len() {
+        return Err(ArnError::Type(format!(
+            "`{op}` cannot multiply a {} matrix by a {} one",
+            size(left),
+            size(right)
+        )));
+    }
+
+    let width = right.first().map_or(0, Vec::len);
+    Ok(left
+        .iter()
+        .map(|row| {
+            (0..width)
+                .map(|j| {
+                    row.iter().zip(right).fold(zero(), |acc, (l, r)| {
+                        acc.arithmetic('+', &l.arithmetic('*', &r[j]))
+                    })
+                })
+                .collect()
+        })
+        .collect())
+}
+
+pub fn identity(n: usize) -> Matrix {
+    (0..n)
+        .map(|i| (0..n).map(|j| Dynamic::from(Int::from(i == j))).collect())
+        .collect()
+}
+
+// <m> multiplied by itself <exp> times, by repeated squaring
+pub fn power(mut m: Matrix, mut exp: u32, op: &str) -> ArnResult<Matrix> {
+    require_square(&m, op)?;
+    let mut result = identity(m.len());
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = multiply(&result, &m, op)?;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            m = multiply(&m, &m, op)?;
+        }
+    }
+    Ok(result)
+}
+
+// Moves the row with the largest value in column <col> (from <col> down) to <col>
+// Gives whether rows were swapped, or None if the column is all 0
+fn pivot(m: &mut Matrix, col: usize, others: Option<&mut Matrix>) -> Option<bool> {
+    let best = (col..m.len()).max_by(|&a, &b| {
+        magnitude(&m[a][col])
+            .cmp_num(&magnitude(&m[b][col]))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    match best {
+        Some(row) if !is_zero(&m[row][col]) => {
+            m.swap(row, col);
+            if let Some(others) = others {
+                others.swap(row, col);
+            }
+            Some(row != col)
+        }
+        _ => None,
+    }
+}
+
+// Bareiss' algorithm, so the determinant of an integer matrix stays exact
+pub fn determinant(mut m: Matrix, op: &str) -> ArnResult<Dynamic> {
+    require_square(&m, op)?;
+    let n = m.len();
+    let mut negate = false;
+    let mut prev = Dynamic::from(Int::from(1));
+
+    for k in 0..n {
+        // Every swap flips the sign
+        match pivot(&mut m, k, None) {
+            Some(swapped) => negate ^= swapped,
+            None => return Ok(zero()),
+        }
+        for i in k + 1..n {
+            for j in k + 1..n {
+                let cross = m[i][j]
+                    .arithmetic('*', &m[k][k])
+                    .arithmetic('-', &m[i][k].arithmetic('*', &m[k][j]));
+                m[i][j] = cross.arithmetic('/', &prev);
+            }
+        }
+        prev = m[k][k].clone();
+    }
+
+    let det = m.last().and_then(|row| row.last()).cloned().unwrap_or(prev);
+    Ok(if negate {
+        zero().arithmetic('-', &det)
+    } else {
+        det
+    })
+}
+
+// Gauss-Jordan elimination on <m> next to the identity matrix
+pub fn inverse(mut m: Matrix, op: &str) -> ArnResult<Matrix> {
+    require_square(&m, op)?;
+    let n = m.len();
+    let mut inv = identity(n);
+
+    for k in 0..n {
+        if pivot(&mut m, k, Some(&mut inv)).is_none() {
+            return Err(ArnError::Type(format!(
+                "`{op}` cannot invert a singular matrix"
+            )));
+        }
+
+        let lead = m[k][k].clone();
+        for j in 0..n {
+            m[k][j] = m[k][j].arithmetic('/', &lead);
+            inv[k][j] = inv[k][j].arithmetic('/', &lead);
+        }
+        for i in (0..n).filter(|&i| i != k) {
+            let factor = m[i][k].clone();
+            if is_zero(&factor) {
+                continue;
+            }
+            for j in 0..n {
+                m[i][j] = m[i][j].arithmetic('-', &factor.arithmetic('*', &m[k][j]));
+                inv[i][j] = inv[i][j].arithmetic('-', &factor.arithmetic('*', &inv[k][j]));
+            }
+        }
+    }
+    Ok(inv)
+}
+
+#[inline]
+pub fn into_dynamic(m: Matrix) -> Dynamic {
+    Dynamic::from(m.into_iter().map(Dynamic::from).collect::<Vec<_>>())
+}
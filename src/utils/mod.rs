@@ -1,10 +1,17 @@
+use self::error::{ArnError, ArnResult};
 use self::tokens::*;
 
+pub mod base;
+pub mod combinatorics;
 pub mod compress;
 pub mod consts;
 pub mod dict;
 pub mod env;
+pub mod error;
+pub mod io;
+pub mod matrix;
 pub mod num;
+pub mod primes;
 pub mod tokens;
 pub mod types;
 
@@ -17,8 +24,8 @@ pub fn create_str_range(s: usize, n: usize) -> String {
 pub fn sum_rank(start: i128, rest: &[Token]) -> i128 {
     start
         + rest.iter().cloned().fold(0_i128, |acc, op| {
-            if let Token::Operator(_, rank) = op {
-                acc + rank.0 as i128 + rank.1 as i128
+            if let Token::Operator(_, rank, _) = op {
+                acc + i128::from(rank.0) + i128::from(rank.1)
             } else {
                 acc
             }
@@ -26,8 +33,8 @@ pub fn sum_rank(start: i128, rest: &[Token]) -> i128 {
 }
 
 // Systematically replace `_` with values from entries
-pub fn traverse_replace(entries: &mut Vec<Node>, tree: Node) -> Node {
-    match &tree {
+pub fn traverse_replace(entries: &mut Vec<Node>, tree: Node) -> ArnResult<Node> {
+    Ok(match &tree {
         Node::Block(body, nm) => {
             // Blocks that use a key of `_` should not be messed with
             let new_body = if nm.is_none() || nm.as_ref().unwrap() == "_" {
@@ -35,7 +42,7 @@ pub fn traverse_replace(entries: &mut Vec<Node>, tree: Node) -> Node {
             } else {
                 body.iter()
                     .map(|n| traverse_replace(entries, n.clone()))
-                    .collect()
+                    .collect::<ArnResult<_>>()?
             };
             Node::Block(new_body, nm.clone())
         }
@@ -44,12 +51,19 @@ pub fn traverse_replace(entries: &mut Vec<Node>, tree: Node) -> Node {
 
         Node::Number(_) => tree,
 
-        Node::Variable(v) => {
+        Node::Integer(_) => tree,
+
+        Node::Rational(_) => tree,
+
+        Node::Complex(_) => tree,
+
+        Node::Variable(v, _) => {
             if v == "_" {
-                entries
-                    .pop()
-                    .expect("Too many `_` found in block by utils::traverse_replace")
-                    .clone()
+                entries.pop().ok_or_else(|| {
+                    ArnError::IndexOutOfRange(
+                        "block uses `_` more times than there are values to fill it".to_owned(),
+                    )
+                })?
             } else {
                 tree
             }
@@ -59,11 +73,11 @@ pub fn traverse_replace(entries: &mut Vec<Node>, tree: Node) -> Node {
             let new_body = body
                 .iter()
                 .map(|n| traverse_replace(entries, n.clone()))
-                .collect();
+                .collect::<ArnResult<_>>()?;
             Node::Group(new_body)
         }
 
-        Node::Op(n, largs, rargs) => {
+        Node::Op(n, largs, rargs, span) => {
             // Fold shouldn't have the left args replaced
             let nl = if n == "\\" || n == ":\\" {
                 largs.clone()
@@ -71,47 +85,35 @@ pub fn traverse_replace(entries: &mut Vec<Node>, tree: Node) -> Node {
                 largs
                     .iter()
                     .map(|n| traverse_replace(entries, n.clone()))
-                    .collect()
+                    .collect::<ArnResult<_>>()?
             };
             let nr = rargs
                 .iter()
                 .map(|n| traverse_replace(entries, n.clone()))
-                .collect();
-            Node::Op(n.clone(), nl, nr)
+                .collect::<ArnResult<_>>()?;
+            Node::Op(n.clone(), nl, nr, *span)
         }
 
         Node::Sequence(body, block, len) => {
             let new_body = body
                 .iter()
                 .map(|n| traverse_replace(entries, n.clone()))
-                .collect();
-            let new_len = len
-                .as_ref()
-                .map(|n| Box::new(traverse_replace(entries, n.as_ref().clone())));
+                .collect::<ArnResult<_>>()?;
+            let new_len = match len {
+                Some(n) => Some(Box::new(traverse_replace(entries, n.as_ref().clone())?)),
+                None => None,
+            };
             Node::Sequence(new_body, block.clone(), new_len)
         }
 
-        _ => unimplemented!(),
-    }
-}
+        Node::Map(body) => {
+            let new_body = body
+                .iter()
+                .map(|n| traverse_replace(entries, n.clone()))
+                .collect::<ArnResult<_>>()?;
+            Node::Map(new_body)
+        }
 
-// Create non-base10 matrix
-pub fn nbase_padded<T: FnMut(String) -> String>(
-    mut orig: self::types::Dynamic,
-    mut f: T,
-) -> Vec<String> {
-    if !orig.is_array() {
-        orig = self::types::Dynamic::from([orig]);
-    }
-    // This should be inferred, but isn't
-    let mut v: Vec<String> = orig
-        .literal_array()
-        .map(|d| f(d.literal_string()))
-        .collect();
-    let max = v.iter().map(|n| n.len()).max().unwrap();
-    v = v
-        .iter()
-        .map(|n| format!("{:0>size$}", n, size = max))
-        .collect();
-    v
+        Node::CmpString(..) => tree,
+    })
 }
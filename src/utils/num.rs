@@ -1,9 +1,17 @@
-use rug::Float;
+use rug::ops::Pow;
+use rug::{Complex, Float, Integer, Rational};
 
-use crate::FLOAT_PRECISION;
+use super::error::{ArnError, ArnResult};
+use super::types::Dynamic;
 
 // Alias
 pub type Num = Float;
+pub type Int = Integer;
+pub type Rat = Rational;
+pub type Cpx = Complex;
+
+// Integer literals with an exponent larger than this are left as floats
+const MAX_INT_EXPONENT: u32 = 10_000;
 
 pub fn is_arn_num(string: &str) -> bool {
     let count_ = string.matches('_').count();
@@ -42,7 +50,7 @@ pub fn is_arn_num(string: &str) -> bool {
     }
 }
 
-pub fn parse_arn_num(string: &str) -> Result<Num, Box<dyn std::error::Error>> {
+pub fn parse_arn_num(string: &str) -> ArnResult<Num> {
     let mut num = String::with_capacity(string.len() + 1);
     if string.starts_with('e') {
         num.push('1');
@@ -55,17 +63,43 @@ pub fn parse_arn_num(string: &str) -> Result<Num, Box<dyn std::error::Error>> {
     }
     num = num.replace('_', "-");
 
-    Ok({
-        let num = Num::parse(&num)?;
-        Num::with_val(*FLOAT_PRECISION, num)
-    })
+    let parsed =
+        Num::parse(&num).map_err(|_| ArnError::Lex(format!("invalid number `{string}`")))?;
+    Ok(Num::with_val(crate::float_precision(), parsed))
+}
+
+// Parses literals without a negative exponent as exact integers
+pub fn parse_arn_int(string: &str) -> Option<Int> {
+    let (mantissa, exponent) = match string.split_once('e') {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<u32>().ok()?),
+        None => (string, 0),
+    };
+    if exponent > MAX_INT_EXPONENT {
+        return None;
+    }
+
+    let mantissa = match mantissa {
+        "" => Int::from(1),
+        "_" => Int::from(-1),
+        _ => mantissa.replace('_', "-").parse::<Int>().ok()?,
+    };
+    Some(mantissa * Int::from(10).pow(exponent))
+}
+
+// Whole results are always exact, fractions only when the rational mode keeps them
+pub fn keep_exact(n: Rat) -> Option<Rat> {
+    if *n.denom() == 1 || crate::rational_mode() != crate::RationalMode::Off {
+        Some(n)
+    } else {
+        None
+    }
 }
 
 #[inline]
-pub fn to_u32(env: &super::types::Env, n: &super::tokens::Node) -> u32 {
-    crate::parser::parse_node(std::rc::Rc::clone(env), n)
-        .literal_num()
+pub fn to_u32(env: &super::types::Env, n: &super::tokens::Node) -> ArnResult<u32> {
+    let num = crate::parser::parse_node(std::rc::Rc::clone(env), n)?.literal_num();
+    num.clone()
         .floor()
         .to_u32_saturating_round(rug::float::Round::Down)
-        .unwrap()
+        .ok_or_else(|| ArnError::Type(format!("`{}` is not a valid count", Dynamic::from(num))))
 }
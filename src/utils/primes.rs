@@ -0,0 +1,169 @@
+// Number theory on integers of any size
+
+use rug::integer::IsPrime;
+use rug::ops::Pow;
+
+use super::error::{ArnError, ArnResult};
+use super::num::Int;
+
+// Miller-Rabin rounds, the chance of a composite passing is below 4^-30
+const ROUNDS: u32 = 30;
+
+// Factors below this are found by trial division
+const TRIAL_LIMIT: u32 = 1000;
+
+#[inline]
+pub fn is_prime(n: &Int) -> bool {
+    n.is_probably_prime(ROUNDS) != IsPrime::No
+}
+
+// A non-trivial factor of the odd composite <n>, with Pollard's rho
+fn pollard_rho(n: &Int) -> Int {
+    let mut offset = Int::from(1);
+    loop {
+        let step = |x: &Int| (Int::from(x.square_ref()) + &offset) % n;
+        let (mut tortoise, mut hare) = (Int::from(2), Int::from(2));
+        let mut factor = Int::from(1);
+        while factor == 1 {
+            tortoise = step(&tortoise);
+            hare = step(&step(&hare));
+            factor = Int::from(&tortoise - &hare).abs().gcd(n);
+        }
+        // The cycle closed without finding a factor, try another polynomial
+        if factor != *n {
+            return factor;
+        }
+        offset += 1;
+    }
+}
+
+// The prime factors of <n> in ascending order, each repeated as often as it divides <n>
+pub fn factorize(n: &Int) -> ArnResult<Vec<Int>> {
+    if *n == 0 {
+        return Err(ArnError::Type("`0` has no prime factorization".to_owned()));
+    }
+
+    let mut rest = Int::from(n.abs_ref());
+    let mut factors = Vec::new();
+    for p in (2..TRIAL_LIMIT).filter(|p| *p == 2 || p % 2 == 1) {
+        while rest.is_divisible_u(p) {
+            rest /= p;
+            factors.push(Int::from(p));
+        }
+    }
+
+    let mut composites = vec![rest];
+    while let Some(m) = composites.pop() {
+        if m == 1 {
+            continue;
+        }
+        if is_prime(&m) {
+            factors.push(m);
+        } else if m.is_perfect_square() {
+            // Rho struggles with the square of a prime
+            let root = m.sqrt();
+            composites.push(root.clone());
+            composites.push(root);
+        } else {
+            let d = pollard_rho(&m);
+            composites.push(Int::from(&m / &d));
+            composites.push(d);
+        }
+    }
+
+    factors.sort();
+    Ok(factors)
+}
+
+// Each distinct prime factor of <n> with its multiplicity
+pub fn factor_powers(n: &Int) -> ArnResult<Vec<(Int, u32)>> {
+    let mut powers: Vec<(Int, u32)> = Vec::new();
+    for p in factorize(n)? {
+        match powers.last_mut() {
+            Some((last, k)) if *last == p => *k += 1,
+            _ => powers.push((p, 1)),
+        }
+    }
+    Ok(powers)
+}
+
+// Every positive divisor of <n>, ascending
+pub fn divisors(n: &Int) -> ArnResult<Vec<Int>> {
+    let mut divs = vec![Int::from(1)];
+    for (p, k) in factor_powers(n)? {
+        let mut next = Vec::with_capacity(divs.len() * (k as usize + 1));
+        for d in &divs {
+            let mut power = d.clone();
+            next.push(power.clone());
+            for _ in 0..k {
+                power *= &p;
+                next.push(power.clone());
+            }
+        }
+        divs = next;
+    }
+    divs.sort();
+    Ok(divs)
+}
+
+// Euler's totient, how many numbers up to <n> are coprime to it
+pub fn totient(n: &Int) -> ArnResult<Int> {
+    if *n == 0 {
+        return Ok(Int::new());
+    }
+    Ok(factor_powers(n)?
+        .into_iter()
+        .fold(Int::from(1), |acc, (p, k)| {
+            acc * Int::from((&p).pow(k - 1)) * (p - 1)
+        }))
+}
+
+// The sum of the divisors of <n>
+pub fn sigma(n: &Int) -> ArnResult<Int> {
+    if *n == 0 {
+        return Err(ArnError::Type(
+            "`0` has infinitely many divisors".to_owned(),
+        ));
+    }
+    Ok(factor_powers(n)?
+        .into_iter()
+        .fold(Int::from(1), |acc, (p, k)| {
+            acc * ((Int::from((&p).pow(k + 1)) - 1) / (p - 1))
+        }))
+}
+
+// The <n>th prime, counting 2 as the first
+pub fn nth_prime(n: &Int) -> ArnResult<Int> {
+    match n.to_usize() {
+        Some(0) => Err(ArnError::Type("there is no 0th prime".to_owned())),
+        Some(i) if *n < u32::MAX => Ok(Int::from(primal::StreamingSieve::nth_prime(i))),
+        _ => Err(ArnError::Type(format!(
+            "`{}` is not a valid prime index",
+            n.to_string().replace('-', "_")
+        ))),
+    }
+}
+
+// The smallest prime above <n>
+pub fn next_prime(n: &Int) -> Int {
+    if *n < 2 {
+        Int::from(2)
+    } else {
+        n.clone().next_prime()
+    }
+}
+
+// The largest prime below <n>
+pub fn prev_prime(n: &Int) -> ArnResult<Int> {
+    if *n <= 2 {
+        return Err(ArnError::Type(format!(
+            "there is no prime below `{}`",
+            n.to_string().replace('-', "_")
+        )));
+    }
+    let mut p = Int::from(n - 1);
+    while !is_prime(&p) {
+        p -= 1;
+    }
+    Ok(p)
+}
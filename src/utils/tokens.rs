@@ -1,6 +1,33 @@
 use std::fmt::{self, Display, Formatter};
 
-use super::num::Num;
+use super::num::{Cpx, Int, Num, Rat};
+
+// Position of a token in the program, counted in chars
+// Tokens that were implied (such as an inserted `_`) have an empty span
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    #[inline]
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+}
+
+// Spans are only used for diagnostics, they don't change what a token means
+impl PartialEq for Span {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
@@ -13,14 +40,20 @@ pub enum Token {
     /// Numeric Node
     Number(Num),
 
+    /// Exact integer Node
+    Integer(Int),
+
+    /// Imaginary number Node
+    Complex(Box<Cpx>),
+
     /// Variable Node
-    Variable(String),
+    Variable(String, Span),
 
     /// A block that contains some code
     Block(Vec<Token>, char, Option<String>),
 
     /// Operator
-    Operator(String, (i32, i32)),
+    Operator(String, (i32, i32), Span),
 
     /// Comma
     Comma,
@@ -31,7 +64,7 @@ pub enum Token {
 pub enum Node {
     /// A fix is any operation (usually denoted by punctuation)
     /// that takes in arguments on the left and/or right.
-    Op(String, Vec<Node>, Vec<Node>),
+    Op(String, Vec<Node>, Vec<Node>, Span),
 
     /// String Node
     String(String),
@@ -42,8 +75,17 @@ pub enum Node {
     /// Numeric Node
     Number(Num),
 
+    /// Exact integer Node
+    Integer(Int),
+
+    /// Exact fraction Node
+    Rational(Rat),
+
+    /// Complex number Node
+    Complex(Box<Cpx>),
+
     /// Variable Node
-    Variable(String),
+    Variable(String, Span),
 
     /// A Group `( ... )`
     Group(Vec<Node>),
@@ -54,25 +96,29 @@ pub enum Node {
     /// A Sequence `[ ... ]`
     /// Body, block, size
     Sequence(Vec<Node>, Box<Node>, Option<Box<Node>>),
+
+    /// A Map `.[ ... ]`
+    /// Keys alternating with their values
+    Map(Vec<Node>),
 }
 
 // Hacky but it'll do
 impl Display for Node {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match &self {
-            Self::Op(val, l, r) => {
+            Self::Op(val, l, r, _) => {
                 for item in l {
-                    write!(f, "{}", item)?;
+                    write!(f, "{item}")?;
                 }
-                write!(f, "{} ", val)?;
+                write!(f, "{val} ")?;
                 for item in r {
-                    write!(f, "{}", item)?;
+                    write!(f, "{item}")?;
                 }
 
                 Ok(())
             }
 
-            Self::String(st) => write!(f, "\"{}\" ", st),
+            Self::String(st) => write!(f, "\"{st}\" "),
 
             Self::CmpString(cst, chr) => {
                 write!(f, "\"{}\"", super::dict::decompress(cst, *chr == '\''))
@@ -80,23 +126,33 @@ impl Display for Node {
 
             Self::Number(num) => write!(f, "{} ", super::types::Dynamic::from(num.clone())),
 
-            Self::Variable(st) => write!(f, "{} ", st),
+            Self::Integer(int) => write!(f, "{} ", super::types::Dynamic::from(int.clone())),
+
+            Self::Rational(rat) => write!(f, "({}) ", rat.to_string().replace('-', "_")),
+
+            Self::Complex(cpx) => write!(
+                f,
+                "({}) ",
+                super::types::Dynamic::from(cpx.as_ref().clone())
+            ),
+
+            Self::Variable(st, _) => write!(f, "{st} "),
 
             Self::Group(nodes) => {
                 write!(f, "(")?;
                 for node in nodes {
-                    write!(f, "{}", node)?;
+                    write!(f, "{node}")?;
                 }
                 write!(f, ")")
             }
 
             Self::Block(nodes, name) => {
                 if let Some(name) = name {
-                    write!(f, "{}", name)?;
+                    write!(f, "{name}")?;
                 }
                 write!(f, "{{")?;
                 for node in nodes {
-                    write!(f, "{}", node)?;
+                    write!(f, "{node}")?;
                 }
                 write!(f, "}} ")
             }
@@ -104,7 +160,7 @@ impl Display for Node {
             Self::Sequence(entries, block, len) => {
                 write!(f, "[")?;
                 for entry in entries {
-                    write!(f, "{}, ", entry)?;
+                    write!(f, "{entry}, ")?;
                 }
                 write!(f, "{}", block.as_ref())?;
 
@@ -114,6 +170,14 @@ impl Display for Node {
 
                 write!(f, "]")
             }
+
+            Self::Map(entries) => {
+                write!(f, ".[")?;
+                for entry in entries {
+                    write!(f, "{entry}")?;
+                }
+                write!(f, "] ")
+            }
         }
     }
 }
@@ -1,13 +1,15 @@
 #![allow(dead_code)]
 
+use std::collections::{hash_map::DefaultHasher, HashMap};
 use std::fmt::{self, Display, Formatter};
 use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
 use std::{cell::RefCell, cmp::Ordering, rc::Rc};
 
 use super::env::Environment;
-use super::num::{to_u32, Num};
+use super::error::{ArnError, ArnResult};
+use super::num::{to_u32, Cpx, Int, Num, Rat};
 use super::tokens::Node;
-use crate::{FLOAT_PRECISION, OUTPUT_PRECISION};
 
 // Shorthand for this monstrosity
 pub type Env = Rc<RefCell<Environment>>;
@@ -19,33 +21,92 @@ pub enum Val {
 
     Number(Num),
 
+    // Exact, used for integer literals and anything that keeps them whole
+    Integer(Int),
+
+    // Exact fraction, only created in rational mode and never with a denominator of 1
+    Rational(Rat),
+
+    // Never has an imaginary part of 0, those are stored as real numbers
+    Complex(Box<Cpx>),
+
     Boolean(bool),
 
     Array(Box<Sequence>),
 
+    Map(Box<Map>),
+
     Empty,
 }
 
+// Values that compare equal must hash the same, and numbers compare equal to numeric strings
+// and booleans, so everything that can be read as a number is hashed as the float it becomes
+// in a mixed comparison
 impl Hash for Val {
     fn hash<H: Hasher>(&self, state: &mut H) {
+        let prec = crate::float_precision();
         match self {
-            Val::String(s) => s.hash(state),
-            Val::Number(n) => n.clone().floor().to_u32_saturating().unwrap().hash(state),
-            Val::Boolean(b) => b.hash(state),
-            Val::Array(s) => s.as_ref().clone().collect::<Vec<Dynamic>>().hash(state),
+            Val::String(s) => match Num::parse(s) {
+                Ok(n) => hash_float(&Num::with_val(prec, n), state),
+                Err(_) => s.hash(state),
+            },
+            Val::Number(n) => hash_float(n, state),
+            Val::Integer(n) => hash_float(&Num::with_val(prec, n), state),
+            Val::Rational(n) => hash_float(&Num::with_val(prec, n), state),
+            Val::Complex(n) => {
+                hash_float(n.real(), state);
+                hash_float(n.imag(), state);
+            }
+            Val::Boolean(b) => hash_float(&Num::with_val(prec, u8::from(*b)), state),
+            // Infinite sequences never compare equal, so they don't need to be hashed
+            Val::Array(s) if !s.is_finite() => {}
+            Val::Array(s) => s
+                .as_ref()
+                .clone()
+                .map_while(Result::ok)
+                .collect::<Vec<Dynamic>>()
+                .hash(state),
+            Val::Map(m) => m.hash(state),
             Val::Empty => "".hash(state),
         }
     }
 }
 
+// Hashes the exact value of <n>, ignoring its precision
+fn hash_float<H: Hasher>(n: &Num, state: &mut H) {
+    match n.to_integer_exp() {
+        Some((mut mantissa, mut exp)) => {
+            // Equal floats of different precisions only differ in trailing zero bits
+            match mantissa.find_one(0) {
+                Some(zeros) => {
+                    mantissa >>= zeros;
+                    exp += zeros as i32;
+                }
+                None => exp = 0,
+            }
+            mantissa.hash(state);
+            exp.hash(state);
+        }
+        // Infinity or NaN
+        None => n.is_sign_negative().hash(state),
+    }
+}
+
 // Struct that represents types in Arn
-#[derive(Clone, Debug, Hash)]
+#[derive(Clone, Debug)]
 pub struct Dynamic {
     val: Val,
     cur: u8,
 }
 
-#[allow(clippy::clippy::wrong_self_convention)]
+// `cur` is left out, equal values can have different types
+impl Hash for Dynamic {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.val.hash(state);
+    }
+}
+
+#[allow(clippy::wrong_self_convention)]
 impl Dynamic {
     pub fn empty() -> Self {
         Self {
@@ -73,13 +134,23 @@ impl Dynamic {
         *self = self.into_bool();
     }
 
+    // How the value is shown as one line of an array's output
+    pub fn entry_string(&self) -> String {
+        let as_string = self.to_string();
+        if self.is_array() || self.is_map() {
+            as_string.replace('\n', " ")
+        } else {
+            as_string
+        }
+    }
+
     // Cast inner value to a `Val::String`
     pub fn into_string(&self) -> Self {
         match &self.val {
             Val::String(_) => self.clone(),
 
-            Val::Number(_) => Self {
-                val: Val::String(format!("{}", self)),
+            Val::Number(_) | Val::Integer(_) | Val::Rational(_) | Val::Complex(_) => Self {
+                val: Val::String(format!("{self}")),
                 cur: 1,
             },
 
@@ -92,12 +163,18 @@ impl Dynamic {
                 val: Val::String(
                     n.clone()
                         .next()
+                        .and_then(Result::ok)
                         .unwrap_or_else(|| Dynamic::from(""))
                         .literal_string(),
                 ),
                 cur: 1,
             },
 
+            Val::Map(_) => Self {
+                val: Val::String(format!("{self}")),
+                cur: 1,
+            },
+
             Val::Empty => Self {
                 val: Val::String(String::new()),
                 cur: 1,
@@ -108,39 +185,35 @@ impl Dynamic {
     // Cast to `Val::Number`
     pub fn into_num(&self) -> Self {
         match &self.val {
-            Val::String(s) => Self {
-                val: Val::Number(Num::with_val(
-                    *FLOAT_PRECISION,
-                    Num::parse(s).unwrap_or_else(|_| Num::parse("0").unwrap()),
-                )),
-                cur: 2,
-            },
+            Val::String(s) => Self::parse_num(s),
 
-            Val::Number(_) => self.clone(),
+            Val::Number(_) | Val::Integer(_) | Val::Rational(_) | Val::Complex(_) => self.clone(),
 
-            Val::Boolean(b) => Self {
-                val: Val::Number(Num::with_val(*FLOAT_PRECISION, if *b { 1 } else { 0 })),
-                cur: 2,
-            },
+            Val::Boolean(b) => Self::from(Int::from(*b)),
 
-            Val::Array(n) => Self {
-                val: Val::Number(Num::with_val(
-                    *FLOAT_PRECISION,
-                    Num::parse(
-                        n.clone()
-                            .next()
-                            .unwrap_or_else(|| Dynamic::from(""))
-                            .literal_string(),
-                    )
-                    .unwrap_or_else(|_| Num::parse("0").unwrap()),
-                )),
-                cur: 2,
-            },
+            Val::Array(n) => Self::parse_num(
+                &n.clone()
+                    .next()
+                    .and_then(Result::ok)
+                    .unwrap_or_else(|| Dynamic::from(""))
+                    .literal_string(),
+            ),
 
-            Val::Empty => Self {
-                val: Val::Number(Num::with_val(*FLOAT_PRECISION, 0)),
-                cur: 2,
-            },
+            // The number of entries
+            Val::Map(m) => Self::from(Int::from(m.len())),
+
+            Val::Empty => Self::from(Int::new()),
+        }
+    }
+
+    // Whole numbers are read exactly, anything that can't be read is 0
+    fn parse_num(s: &str) -> Self {
+        if let Ok(n) = s.parse::<Int>() {
+            Self::from(n)
+        } else if let Ok(n) = Num::parse(s) {
+            Self::from(Num::with_val(crate::float_precision(), n))
+        } else {
+            Self::from(Int::new())
         }
     }
 
@@ -157,18 +230,39 @@ impl Dynamic {
                 cur: 3,
             },
 
+            Val::Integer(n) => Self {
+                val: Val::Boolean(*n != 0),
+                cur: 3,
+            },
+
+            Val::Rational(n) => Self {
+                val: Val::Boolean(*n != 0),
+                cur: 3,
+            },
+
+            Val::Complex(n) => Self {
+                val: Val::Boolean(!n.real().is_zero() || !n.imag().is_zero()),
+                cur: 3,
+            },
+
             Val::Boolean(_) => self.clone(),
 
             Val::Array(n) => Self {
                 val: Val::Boolean(
                     n.clone()
                         .next()
+                        .and_then(Result::ok)
                         .unwrap_or_else(|| Dynamic::from(false))
                         .literal_bool(),
                 ),
                 cur: 3,
             },
 
+            Val::Map(m) => Self {
+                val: Val::Boolean(!m.is_empty()),
+                cur: 3,
+            },
+
             Val::Empty => Self {
                 val: Val::Boolean(false),
                 cur: 3,
@@ -202,12 +296,21 @@ impl Dynamic {
                 }
             }
 
-            Val::Number(_) => Dynamic::from(format!("{}", self)).into_array(),
+            Val::Number(_) | Val::Integer(_) | Val::Rational(_) | Val::Complex(_) => {
+                Dynamic::from(format!("{self}")).into_array()
+            }
 
-            Val::Boolean(_) => Dynamic::from(format!("{}", self)).into_array(),
+            Val::Boolean(_) => Dynamic::from(format!("{self}")).into_array(),
 
             Val::Array(_) => self.clone(),
 
+            // Pairs of key and value
+            Val::Map(m) => Self::from(
+                m.iter()
+                    .map(|(k, v)| Self::from([k.clone(), v.clone()]))
+                    .collect::<Vec<_>>(),
+            ),
+
             Val::Empty => Self {
                 val: Val::Array(Box::new(Sequence::from_vec::<String>(
                     &[],
@@ -223,10 +326,53 @@ impl Dynamic {
     pub fn literal_num(self) -> Num {
         match self.val {
             Val::Number(n) => n,
+            Val::Integer(n) => Num::with_val(crate::float_precision(), n),
+            Val::Rational(n) => Num::with_val(crate::float_precision(), n),
+            // Anything that needs a real number uses the real part
+            Val::Complex(n) => n.into_real_imag().0,
             _ => self.into_num().literal_num(),
         }
     }
 
+    // Real numbers have an imaginary part of 0
+    #[inline]
+    pub fn literal_complex(self) -> Cpx {
+        match self.val {
+            Val::Complex(n) => *n,
+            _ => Cpx::with_val(crate::float_precision(), self.literal_num()),
+        }
+    }
+
+    // The exact value, if this is (or converts to) a whole number
+    #[inline]
+    pub fn literal_int(self) -> Option<Int> {
+        match self.val {
+            Val::Integer(n) => Some(n),
+            Val::Number(_) | Val::Rational(_) | Val::Complex(_) => None,
+            _ => self.into_num().literal_int(),
+        }
+    }
+
+    // The exact value, if this is (or converts to) an integer or fraction
+    #[inline]
+    pub fn literal_rat(self) -> Option<Rat> {
+        match self.val {
+            Val::Integer(n) => Some(Rat::from(n)),
+            Val::Rational(n) => Some(n),
+            Val::Number(_) | Val::Complex(_) => None,
+            _ => self.into_num().literal_rat(),
+        }
+    }
+
+    // Like `literal_rat`, but only for values that already are exact numbers
+    fn exact(&self) -> Option<Rat> {
+        match &self.val {
+            Val::Integer(n) => Some(Rat::from(n)),
+            Val::Rational(n) => Some(n.clone()),
+            _ => None,
+        }
+    }
+
     #[inline]
     pub fn literal_string(self) -> String {
         match self.val {
@@ -251,6 +397,24 @@ impl Dynamic {
         }
     }
 
+    // Arrays (and anything that converts to one) are keyed by index
+    pub fn literal_map(self) -> ArnResult<Map> {
+        if let Val::Map(m) = self.val {
+            return Ok(*m);
+        }
+
+        let seq = self.literal_array();
+        if !seq.is_finite() {
+            return Err(ArnError::InfiniteSequence(
+                "an infinite sequence cannot be used as a map".to_owned(),
+            ));
+        }
+
+        seq.enumerate()
+            .map(|(i, v)| Ok((Self::from(Int::from(i)), v?)))
+            .collect()
+    }
+
     #[inline]
     pub fn is_string(&self) -> bool {
         self.cur == 1
@@ -271,6 +435,16 @@ impl Dynamic {
         self.cur == 4
     }
 
+    #[inline]
+    pub fn is_map(&self) -> bool {
+        self.cur == 5
+    }
+
+    #[inline]
+    pub fn is_complex(&self) -> bool {
+        matches!(self.val, Val::Complex(_))
+    }
+
     // Mutate inner `Val::String`
     pub fn mutate_string<T: FnOnce(String) -> String>(&self, f: T) -> Self {
         match &self.val {
@@ -283,12 +457,109 @@ impl Dynamic {
     // Mutate inner `Val::Number`
     pub fn mutate_num<T: FnOnce(Num) -> Num>(&self, f: T) -> Self {
         match &self.val {
-            Val::Number(n) => Self::from(f(n.clone())),
+            // Rounded to the working precision, which may differ from the one it was created with
+            Val::Number(n) => Self::from(f(Num::with_val(crate::float_precision(), n))),
+
+            Val::Integer(n) => Self::from(f(Num::with_val(crate::float_precision(), n))),
+
+            Val::Rational(n) => Self::from(f(Num::with_val(crate::float_precision(), n))),
+
+            Val::Complex(_) => Self::from(f(self.clone().literal_num())),
 
             _ => self.into_num().mutate_num(f),
         }
     }
 
+    // Mutate the number, staying exact while <exact> gives a result (see `num::keep_exact`)
+    pub fn mutate_exact<T, U>(&self, exact: T, float: U) -> Self
+    where
+        T: FnOnce(Rat) -> Option<Rat>,
+        U: FnOnce(Num) -> Num,
+    {
+        let num = self.into_num();
+        match num.clone().literal_rat().and_then(exact) {
+            Some(n) => Self::from(n),
+            None => num.mutate_num(float),
+        }
+    }
+
+    // Combine two numbers, staying exact while both are and <exact> gives a result
+    pub fn zip_num<T, U>(&self, other: &Self, exact: T, float: U) -> Self
+    where
+        T: FnOnce(Rat, Rat) -> Option<Rat>,
+        U: FnOnce(Num, Num) -> Num,
+    {
+        let (left, right) = (self.into_num(), other.into_num());
+        if let (Some(l), Some(r)) = (left.clone().literal_rat(), right.clone().literal_rat()) {
+            if let Some(n) = exact(l, r) {
+                return Self::from(n);
+            }
+        }
+
+        left.mutate_num(|l| float(l, right.literal_num()))
+    }
+
+    // Combine two numbers as complex numbers, if either of them is one
+    pub fn zip_complex<T: FnOnce(Cpx, Cpx) -> Cpx>(&self, other: &Self, f: T) -> Option<Self> {
+        if self.is_complex() || other.is_complex() {
+            Some(Self::from(f(
+                self.clone().literal_complex(),
+                other.clone().literal_complex(),
+            )))
+        } else {
+            None
+        }
+    }
+
+    // <self> + - * / <other>, complex if either side is and exact while possible
+    pub fn arithmetic(&self, op: char, other: &Self) -> Self {
+        match op {
+            '+' => self
+                .zip_complex(other, |l, r| l + r)
+                .unwrap_or_else(|| self.zip_num(other, |l, r| Some(l + r), |l, r| l + r)),
+            '-' => self
+                .zip_complex(other, |l, r| l - r)
+                .unwrap_or_else(|| self.zip_num(other, |l, r| Some(l - r), |l, r| l - r)),
+            '*' => self
+                .zip_complex(other, |l, r| l * r)
+                .unwrap_or_else(|| self.zip_num(other, |l, r| Some(l * r), |l, r| l * r)),
+            _ => self.zip_complex(other, |l, r| l / r).unwrap_or_else(|| {
+                self.zip_num(
+                    other,
+                    |l, r| (r != 0).then(|| l / r).and_then(super::num::keep_exact),
+                    |l, r| l / r,
+                )
+            }),
+        }
+    }
+
+    // Change a complex number, real numbers are left to the caller
+    pub fn mutate_complex<T: FnOnce(Cpx) -> Cpx>(&self, f: T) -> Option<Self> {
+        self.is_complex()
+            .then(|| Self::from(f(self.clone().literal_complex())))
+    }
+
+    // Numeric comparison, exact between integers and fractions
+    pub fn cmp_num(&self, other: &Self) -> Option<Ordering> {
+        let (left, right) = (self.into_num(), other.into_num());
+        if let (Some(l), Some(r)) = (left.clone().literal_rat(), right.clone().literal_rat()) {
+            return Some(l.cmp(&r));
+        }
+        if left.is_complex() || right.is_complex() {
+            return (left == right).then_some(Ordering::Equal);
+        }
+
+        left.literal_num().partial_cmp(&right.literal_num())
+    }
+
+    // Whole floats (such as the result of a floor) become exact integers
+    pub fn into_exact(self) -> Self {
+        match &self.val {
+            Val::Number(n) if n.is_integer() => Self::from(n.to_integer().unwrap()),
+            _ => self,
+        }
+    }
+
     // Mutate inner `Val::Boolean`
     pub fn mutate_bool<T: FnOnce(bool) -> bool>(&self, f: T) -> Self {
         match &self.val {
@@ -311,26 +582,59 @@ impl Dynamic {
     }
 
     // Convert to Node
-    pub fn into_node(self) -> Node {
-        match self.val {
+    pub fn into_node(self) -> ArnResult<Node> {
+        Ok(match self.val {
             Val::String(s) => Node::String(s),
             Val::Number(n) => Node::Number(n),
-            Val::Boolean(b) => Node::Number(Num::with_val(*FLOAT_PRECISION, if b { 1 } else { 0 })),
+            Val::Integer(n) => Node::Integer(n),
+            Val::Rational(n) => Node::Rational(n),
+            Val::Complex(n) => Node::Complex(n),
+            Val::Boolean(b) => Node::Integer(Int::from(b)),
             Val::Array(s) => {
                 if !s.is_finite() {
-                    panic!("Cannot convert infinite sequence into Node");
+                    return Err(ArnError::InfiniteSequence(
+                        "an infinite sequence cannot be substituted into a block".to_owned(),
+                    ));
                 }
 
                 let s = s.as_ref().clone();
+                let values = s.cstr.borrow().values.clone();
                 Node::Sequence(
-                    s.cstr.iter().cloned().map(Dynamic::into_node).collect(),
+                    values
+                        .iter()
+                        .cloned()
+                        .map(Dynamic::into_node)
+                        .collect::<ArnResult<_>>()?,
                     Box::new(s.block),
-                    s.length
-                        .map(|n| Box::new(Node::Number(Num::with_val(*FLOAT_PRECISION, n)))),
+                    s.length.map(|n| Box::new(Node::Integer(Int::from(n)))),
                 )
             }
+            Val::Map(m) => Node::Map(
+                m.iter()
+                    .flat_map(|(k, v)| [k.clone(), v.clone()])
+                    .map(Dynamic::into_node)
+                    .collect::<ArnResult<_>>()?,
+            ),
             Val::Empty => unreachable!(),
-        }
+        })
+    }
+}
+
+// Rounds <n> to the output precision, without trailing zeros
+fn format_float(n: &Num) -> String {
+    let s = n.to_string_radix_round(
+        10,
+        Some(crate::output_precision()),
+        rug::float::Round::Nearest,
+    );
+
+    if s.contains('.') && !s.contains('e') {
+        // First remove trailing zeros, then the dot if that was everything
+        s.trim_end_matches('0')
+            .trim_end_matches('.')
+            .replace('-', "_")
+    } else {
+        s.replace('-', "_")
     }
 }
 
@@ -338,47 +642,54 @@ impl Dynamic {
 impl Display for Dynamic {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match &self.val {
-            Val::String(s) => write!(f, "{}", s),
-
-            Val::Number(n) => {
-                let s = n.to_string_radix_round(
-                    10,
-                    Some(*OUTPUT_PRECISION),
-                    rug::float::Round::Nearest,
-                );
-
-                write!(
-                    f,
-                    "{}",
-                    if s.contains('.') && !s.contains('e') {
-                        // First remove trailing zeros, then the dot if that was everything
-                        s.trim_end_matches('0')
-                            .trim_end_matches('.')
-                            .replace('-', "_")
+            Val::String(s) => write!(f, "{s}"),
+
+            Val::Number(n) => write!(f, "{}", format_float(n)),
+
+            Val::Integer(n) => write!(f, "{}", n.to_string().replace('-', "_")),
+
+            Val::Rational(n) => {
+                if crate::rational_mode() == crate::RationalMode::Decimal {
+                    write!(
+                        f,
+                        "{}",
+                        format_float(&Num::with_val(crate::float_precision(), n))
+                    )
+                } else {
+                    write!(f, "{}", n.to_string().replace('-', "_"))
+                }
+            }
+
+            // `a+bi`, which is also how it would be written in a program
+            Val::Complex(n) => {
+                let imag = format_float(&Num::with_val(n.prec().1, n.imag().abs_ref()));
+                if n.real().is_zero() {
+                    let sign = if n.imag().is_sign_negative() { "_" } else { "" };
+                    write!(f, "{sign}{imag}i")
+                } else {
+                    let sign = if n.imag().is_sign_negative() {
+                        '-'
                     } else {
-                        s.replace('-', "_")
-                    }
-                )
+                        '+'
+                    };
+                    write!(f, "{}{sign}{imag}i", format_float(n.real()))
+                }
             }
 
-            Val::Boolean(b) => write!(f, "{}", if *b { 1 } else { 0 }),
+            Val::Boolean(b) => write!(f, "{}", i32::from(*b)),
 
             Val::Array(seq) => {
-                let seq = seq.as_ref().clone();
-                let is_infinite = !seq.is_finite();
-
-                for entry in seq {
-                    let mut as_string = format!("{}", entry);
+                // `io::write_value` reports errors and streams infinite sequences, the output just stops at the first error
+                for entry in seq.as_ref().clone().map_while(Result::ok) {
+                    writeln!(f, "{}", entry.entry_string())?;
+                }
 
-                    if entry.is_array() {
-                        as_string = as_string.replace('\n', " ");
-                    }
+                Ok(())
+            }
 
-                    if is_infinite {
-                        println!("{}", as_string);
-                    } else {
-                        writeln!(f, "{}", as_string)?;
-                    }
+            Val::Map(map) => {
+                for (key, value) in map.iter() {
+                    writeln!(f, "{}: {}", key.entry_string(), value.entry_string())?;
                 }
 
                 Ok(())
@@ -392,11 +703,19 @@ impl Display for Dynamic {
 impl PartialEq for Dynamic {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
+        // Integers and fractions are only exact against each other, anything else compares as a float
+        match (self.exact(), other.exact()) {
+            (Some(s), Some(o)) => return s == o,
+            (Some(_), None) => return Dynamic::from(self.clone().literal_num()) == *other,
+            (None, Some(_)) => return *self == Dynamic::from(other.clone().literal_num()),
+            (None, None) => {}
+        }
+
         match &self.val {
             Val::String(s) => match &other.val {
                 Val::String(o) => s == o,
                 Val::Number(n) => match Num::parse(s) {
-                    Ok(s) => Num::with_val(*FLOAT_PRECISION, s) == n.clone(),
+                    Ok(s) => Num::with_val(crate::float_precision(), s) == n.clone(),
                     Err(_) => false,
                 },
                 Val::Boolean(b) => {
@@ -411,7 +730,7 @@ impl PartialEq for Dynamic {
 
             Val::Number(n) => match &other.val {
                 Val::String(s) => match Num::parse(s) {
-                    Ok(s) => Num::with_val(*FLOAT_PRECISION, s) == n.clone(),
+                    Ok(s) => Num::with_val(crate::float_precision(), s) == n.clone(),
                     Err(_) => false,
                 },
                 Val::Number(o) => n == o,
@@ -437,8 +756,39 @@ impl PartialEq for Dynamic {
                 _ => false,
             },
 
-            Val::Array(_) => todo!(),
+            // Element by element, so nested arrays compare with the same coercions
+            Val::Array(s) => match &other.val {
+                Val::Array(o) => {
+                    // Two infinite sequences can never be told apart
+                    if !s.is_finite() && !o.is_finite() {
+                        return false;
+                    }
+
+                    let mut o = o.as_ref().clone();
+                    for l in s.as_ref().clone() {
+                        match (l, o.next()) {
+                            (Ok(l), Some(Ok(r))) if l == r => {}
+                            _ => return false,
+                        }
+                    }
+                    o.next().is_none()
+                }
+                _ => false,
+            },
+
+            // Never equal to a real number, those have no imaginary part
+            Val::Complex(n) => match &other.val {
+                Val::Complex(o) => n == o,
+                _ => false,
+            },
+
+            // Regardless of the order the keys were inserted in
+            Val::Map(m) => match &other.val {
+                Val::Map(o) => m == o,
+                _ => false,
+            },
 
+            Val::Integer(_) | Val::Rational(_) => unreachable!(),
             Val::Empty => matches!(other.val, Val::Empty),
         }
     }
@@ -450,68 +800,105 @@ impl Eq for Dynamic {}
 impl PartialOrd for Dynamic {
     #[inline]
     fn partial_cmp(&self, other: &Dynamic) -> Option<Ordering> {
-        match &self.val {
-            Val::String(s) => match &other.val {
-                Val::String(o) => s.partial_cmp(o),
-                Val::Number(n) => match Num::parse(s) {
-                    Ok(s) => Num::with_val(*FLOAT_PRECISION, s).partial_cmp(n),
-                    Err(_) => None,
-                },
-                Val::Boolean(b) => {
-                    if *b {
-                        s.partial_cmp(&"1".to_string())
-                    } else {
-                        s.partial_cmp(&"0".to_string())
-                    }
-                }
-                _ => s.partial_cmp(&other.clone().literal_string()),
-            },
+        Some(self.cmp(other))
+    }
+}
 
-            Val::Number(n) => match &other.val {
-                Val::String(s) => match Num::parse(s) {
-                    Ok(s) => n.partial_cmp(&Num::with_val(*FLOAT_PRECISION, s)),
-                    Err(_) => None,
-                },
-                Val::Number(o) => n.partial_cmp(o),
-                Val::Boolean(b) => {
-                    if *b {
-                        n.partial_cmp(&1)
-                    } else {
-                        n.cmp0()
-                    }
+// Every pair of values has an order, so any sequence can be sorted
+// Values sort by kind (see `kind_rank`), numbers, booleans and numeric strings together by value,
+// other strings as text, arrays by length and then element by element
+impl Ord for Dynamic {
+    fn cmp(&self, other: &Dynamic) -> Ordering {
+        let parts = |l: &Num, r: &Num| {
+            l.partial_cmp(r)
+                .unwrap_or_else(|| l.is_nan().cmp(&r.is_nan()))
+        };
+        self.kind_rank()
+            .cmp(&other.kind_rank())
+            .then_with(|| match (&self.val, &other.val) {
+                (Val::Complex(l), Val::Complex(r)) => {
+                    parts(l.real(), r.real()).then_with(|| parts(l.imag(), r.imag()))
                 }
-                _ => n.partial_cmp(&other.clone().literal_num()),
-            },
-
-            Val::Boolean(b) => match &other.val {
-                Val::Number(n) => {
-                    if *b {
-                        Num::with_val(*FLOAT_PRECISION, 1).partial_cmp(&1)
-                    } else {
-                        Num::new(*FLOAT_PRECISION).partial_cmp(n)
+                (Val::String(l), Val::String(r)) if self.kind_rank() == 3 => l.cmp(r),
+                (Val::Array(l), Val::Array(r)) => {
+                    // Infinite and uncounted sequences come after every counted one
+                    let len = |s: &Sequence| {
+                        (s.is_finite() && !s.is_uncounted()).then(|| s.clone().count())
+                    };
+                    match (len(l), len(r)) {
+                        (Some(l_len), Some(r_len)) => l_len.cmp(&r_len).then_with(|| {
+                            l.as_ref()
+                                .clone()
+                                .map_while(Result::ok)
+                                .cmp(r.as_ref().clone().map_while(Result::ok))
+                        }),
+                        (l_len, r_len) => r_len.is_some().cmp(&l_len.is_some()),
                     }
                 }
-                Val::Boolean(n) => b.partial_cmp(n),
-                _ => b.partial_cmp(&other.clone().literal_bool()),
-            },
-
-            Val::Array(s) => match &other.val {
-                Val::Array(o) => {
-                    let s = s.as_ref().clone().count();
-                    let o = o.as_ref().clone().count();
+                (Val::Map(l), Val::Map(r)) => l.iter().cmp(r.iter()),
+                (Val::Empty, Val::Empty) => Ordering::Equal,
+                _ => self.real_cmp(other),
+            })
+    }
+}
 
-                    s.partial_cmp(&o)
-                }
+impl Dynamic {
+    // Kinds of value in the order they sort in
+    fn kind_rank(&self) -> u8 {
+        match &self.val {
+            Val::Empty => 0,
+            Val::Boolean(_) | Val::Integer(_) | Val::Rational(_) | Val::Number(_) => 1,
+            Val::String(s) if Num::parse(s).is_ok() => 1,
+            Val::Complex(_) => 2,
+            Val::String(_) => 3,
+            Val::Array(_) => 4,
+            Val::Map(_) => 5,
+        }
+    }
 
-                _ => s
-                    .as_ref()
-                    .clone()
-                    .collect::<Vec<_>>()
-                    .partial_cmp(&other.clone().literal_array().collect::<Vec<_>>()),
+    // Orders two real values (numbers, booleans as 0 or 1 and numeric strings) by value, exactly
+    // Equal values are ordered by how they are stored, so only identical values are tied
+    // NaN comes after every other number
+    fn real_cmp(&self, other: &Dynamic) -> Ordering {
+        let float = |val: &Dynamic| match &val.val {
+            Val::Number(n) => Some(n.clone()),
+            Val::String(s) => Num::parse(s)
+                .ok()
+                .map(|n| Num::with_val(crate::float_precision(), n)),
+            _ => None,
+        };
+        let exact = |val: &Dynamic| match &val.val {
+            Val::Boolean(b) => Some(Rat::from(u8::from(*b))),
+            _ => val.exact(),
+        };
+        let by_value = match (exact(self), exact(other)) {
+            (Some(l), Some(r)) => l.cmp(&r),
+            (Some(l), None) => float(other).map_or(Ordering::Equal, |r| {
+                r.partial_cmp(&l).map_or(Ordering::Less, Ordering::reverse)
+            }),
+            (None, Some(r)) => float(self).map_or(Ordering::Equal, |l| {
+                l.partial_cmp(&r).unwrap_or(Ordering::Greater)
+            }),
+            (None, None) => match (float(self), float(other)) {
+                (Some(l), Some(r)) => l
+                    .partial_cmp(&r)
+                    .unwrap_or_else(|| l.is_nan().cmp(&r.is_nan())),
+                _ => Ordering::Equal,
             },
-
-            Val::Empty => None,
-        }
+        };
+
+        let storage = |val: &Dynamic| match &val.val {
+            Val::Integer(_) | Val::Rational(_) => 0,
+            Val::Number(_) => 1,
+            Val::Boolean(_) => 2,
+            _ => 3,
+        };
+        by_value
+            .then_with(|| storage(self).cmp(&storage(other)))
+            .then_with(|| match (&self.val, &other.val) {
+                (Val::String(l), Val::String(r)) => l.cmp(r),
+                _ => Ordering::Equal,
+            })
     }
 }
 
@@ -542,6 +929,43 @@ impl From<Num> for Dynamic {
     }
 }
 
+impl From<Int> for Dynamic {
+    fn from(v: Int) -> Self {
+        Self {
+            val: Val::Integer(v),
+            cur: 2,
+        }
+    }
+}
+
+// Fractions that come out whole are stored as integers
+impl From<Rat> for Dynamic {
+    fn from(v: Rat) -> Self {
+        if *v.denom() == 1 {
+            Self::from(v.into_numer_denom().0)
+        } else {
+            Self {
+                val: Val::Rational(v),
+                cur: 2,
+            }
+        }
+    }
+}
+
+// Complex numbers without an imaginary part are stored as real numbers
+impl From<Cpx> for Dynamic {
+    fn from(v: Cpx) -> Self {
+        if v.imag().is_zero() {
+            Self::from(v.into_real_imag().0).into_exact()
+        } else {
+            Self {
+                val: Val::Complex(Box::new(v)),
+                cur: 2,
+            }
+        }
+    }
+}
+
 impl From<bool> for Dynamic {
     fn from(v: bool) -> Self {
         Self {
@@ -611,9 +1035,12 @@ impl From<Sequence> for Dynamic {
     }
 }
 
-impl From<Node> for Dynamic {
-    fn from(v: Node) -> Self {
-        crate::parser::parse_node(Rc::new(RefCell::new(Environment::init())), &v)
+impl From<Map> for Dynamic {
+    fn from(v: Map) -> Self {
+        Self {
+            val: Val::Map(Box::new(v)),
+            cur: 5,
+        }
     }
 }
 
@@ -623,75 +1050,215 @@ impl Into<Node> for Dynamic {
         match self.val {
             Val::String(st) => Node::String(st),
             Val::Number(nm) => Node::Number(nm),
-            Val::Boolean(bl) => {
-                Node::Number(Num::with_val(*FLOAT_PRECISION, if bl { 1 } else { 0 }))
-            }
+            Val::Integer(nm) => Node::Integer(nm),
+            Val::Rational(nm) => Node::Rational(nm),
+            Val::Complex(nm) => Node::Complex(nm),
+            Val::Boolean(bl) => Node::Integer(Int::from(bl)),
             _ => panic!("Cannot convert emtpy value into Node"),
         }
     }
 }
 
+// Terms derived from another sequence or generated natively, only computed once they are needed
+#[derive(Clone, Debug)]
+pub enum Lazy {
+    /// <f> applied to every term of the source, evaluated in <env>
+    Map(Box<Sequence>, Node, Env),
+    /// The terms of the source <f> is truthy for, evaluated in <env>
+    Filter(Box<Sequence>, Node, Env),
+    /// Pairs of terms from both sources
+    Zip(Box<Sequence>, Box<Sequence>),
+    /// Every <step>th term of the source, after skipping the first <skip>
+    Slice(Box<Sequence>, usize, usize),
+    /// A builtin sequence, with the index of the next term and the term before it
+    Named(Named, u32, Option<Int>),
+    /// Permutations, combinations and the like of the values of finite sequences
+    Arrange(Box<super::combinatorics::Arrangements>),
+}
+
+// Infinite sequences computed natively instead of by an Arn block
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Named {
+    /// 2, 3, 5, 7, ...
+    Primes,
+    /// 1, 4, 9, 16, ...
+    Squares,
+    /// 1, 3, 6, 10, ...
+    Triangular,
+    /// 1, 1, 2, 5, 14, ...
+    Catalan,
+    /// 1, 1, 2, 6, 24, ...
+    Factorials,
+    /// 1, 2, 4, 8, ...
+    PowersOfTwo,
+    /// 2, 1, 3, 4, 7, ...
+    Lucas,
+}
+
+impl Named {
+    // The term at <index>, which follows <prev>
+    fn term(self, index: u32, prev: Option<&Int>) -> Int {
+        match self {
+            Named::Primes => prev.map_or_else(|| Int::from(2), super::primes::next_prime),
+            Named::Squares => Int::from(index + 1).square(),
+            Named::Triangular => Int::from(index + 1) * (index + 2) / 2,
+            Named::Catalan => Int::from(Int::binomial_u(2 * index, index)) / (index + 1),
+            Named::Factorials => Int::from(Int::factorial(index)),
+            Named::PowersOfTwo => Int::from(1) << index,
+            Named::Lucas => Int::from(Int::lucas(index)),
+        }
+    }
+}
+
+impl Lazy {
+    fn next_term(&mut self) -> Option<ArnResult<Dynamic>> {
+        match self {
+            Lazy::Map(seq, f, env) => {
+                Some(seq.next()?.and_then(|val| crate::parser::call(env, f, val)))
+            }
+
+            Lazy::Filter(seq, f, env) => {
+                for val in seq.by_ref() {
+                    let keep = val
+                        .clone()
+                        .and_then(|val| crate::parser::call(env, f, val))
+                        .map(Dynamic::literal_bool);
+                    match keep {
+                        Ok(true) => return Some(val),
+                        Ok(false) => {}
+                        Err(e) => return Some(Err(e)),
+                    }
+                }
+                None
+            }
+
+            Lazy::Zip(left, right) => Some(match (left.next()?, right.next()?) {
+                (Ok(l), Ok(r)) => Ok(Dynamic::from([l, r])),
+                (Err(e), _) | (_, Err(e)) => Err(e),
+            }),
+
+            Lazy::Slice(seq, skip, step) => {
+                let term = seq.nth(*skip);
+                *skip = *step - 1;
+                term
+            }
+
+            Lazy::Named(named, index, prev) => {
+                let term = named.term(*index, prev.as_ref());
+                *index += 1;
+                *prev = Some(term.clone());
+                Some(Ok(Dynamic::from(term)))
+            }
+
+            Lazy::Arrange(arrangements) => arrangements.next_term().map(Ok),
+        }
+    }
+}
+
+// Terms computed so far, shared by every clone of a sequence so each term is only computed once
+#[derive(Debug, Default)]
+struct Terms {
+    values: Vec<Dynamic>,
+    // Replaces `block` for sequences derived from another one
+    lazy: Option<Lazy>,
+    // Whether there were terms before the block ran, otherwise it is only given the index
+    seeded: bool,
+}
+
+#[allow(clippy::len_without_is_empty)]
 #[derive(Clone, Debug)]
 pub struct Sequence {
-    pub cstr: Vec<Dynamic>,
     pub length: Option<usize>,
     pub block: Node,
+    cstr: Rc<RefCell<Terms>>,
     unparsed_length: Option<Node>,
-    t_i: Option<isize>,
+    // Finite, but with too many terms to count, so `length` stays None until it is forced
+    uncounted: bool,
+    derived: bool,
+    // End of the terms not yet taken from the back
+    back: Option<usize>,
     env: Option<Env>,
     index: usize,
 }
 
 impl Sequence {
-    pub fn from_iter<T, U>(iter: T, block: Node, length: Option<usize>) -> Self
-    where
-        T: Iterator<Item = U>,
-        Dynamic: From<U>,
-    {
-        let v = iter.map(Dynamic::from).collect();
+    fn with_terms(values: Vec<Dynamic>, block: Node, length: Option<usize>) -> Self {
         Self {
-            cstr: v,
-            unparsed_length: None,
             length,
             block,
-            t_i: None,
+            cstr: Rc::new(RefCell::new(Terms {
+                seeded: !values.is_empty(),
+                values,
+                lazy: None,
+            })),
+            unparsed_length: None,
+            uncounted: false,
+            derived: false,
+            back: None,
             env: None,
             index: 0,
         }
     }
 
+    pub fn from_iter<T, U>(iter: T, block: Node, length: Option<usize>) -> Self
+    where
+        T: Iterator<Item = U>,
+        Dynamic: From<U>,
+    {
+        Self::with_terms(iter.map(Dynamic::from).collect(), block, length)
+    }
+
     pub fn from_vec<T>(v: &[T], block: Node, length: Option<usize>) -> Self
     where
         Dynamic: From<T>,
         T: Clone,
     {
-        let v = v.iter().map(|n| Dynamic::from(n.clone())).collect();
-        Self {
-            cstr: v,
-            unparsed_length: None,
-            length,
+        Self::with_terms(
+            v.iter().map(|n| Dynamic::from(n.clone())).collect(),
             block,
-            t_i: None,
-            env: None,
-            index: 0,
-        }
+            length,
+        )
     }
 
     pub fn from_vec_dyn(v: &[Dynamic], block: Node, length: Option<usize>) -> Self {
+        Self::with_terms(v.to_owned(), block, length)
+    }
+
+    // An infinite sequence whose terms come from <lazy>
+    pub fn lazy(lazy: Lazy) -> Self {
+        let seq = Self::with_terms(Vec::new(), Node::Block(vec![], None), None);
+        seq.cstr.borrow_mut().lazy = Some(lazy);
         Self {
-            cstr: v.to_owned(),
-            unparsed_length: None,
-            length,
-            block,
-            t_i: None,
-            env: None,
-            index: 0,
+            derived: true,
+            ..seq
+        }
+    }
+
+    // A sequence of <length> terms that come from <lazy>
+    pub fn lazy_with_length(lazy: Lazy, length: usize) -> Self {
+        Self {
+            length: Some(length),
+            ..Self::lazy(lazy)
+        }
+    }
+
+    // A finite sequence whose terms come from <lazy> until it runs out, too many to count up front
+    pub fn lazy_uncounted(lazy: Lazy) -> Self {
+        Self {
+            uncounted: true,
+            ..Self::lazy(lazy)
         }
     }
 
     #[inline]
     pub fn is_finite(&self) -> bool {
-        self.length.is_some() || self.unparsed_length.is_some()
+        self.length.is_some() || self.unparsed_length.is_some() || self.uncounted
+    }
+
+    // Whether the sequence ends but its length is not known
+    #[inline]
+    pub fn is_uncounted(&self) -> bool {
+        self.uncounted && self.length.is_none()
     }
 
     #[inline]
@@ -699,6 +1266,12 @@ impl Sequence {
         self.length
     }
 
+    // The term at <index>, if it has been computed (by this sequence or any clone of it)
+    #[inline]
+    pub fn cached(&self, index: usize) -> Option<Dynamic> {
+        self.cstr.borrow().values.get(index).cloned()
+    }
+
     #[inline]
     pub fn set_env(&mut self, env: Env) {
         self.env = Some(Rc::new(env.as_ref().clone()));
@@ -707,88 +1280,276 @@ impl Sequence {
     #[inline]
     pub fn set_env_self(self, env: Env) -> Self {
         Self {
-            cstr: self.cstr,
-            unparsed_length: self.unparsed_length,
-            length: self.length,
-            block: self.block,
-            t_i: self.t_i,
             env: Some(env),
-            index: self.index,
+            ..self
         }
     }
 
-    fn traverse_replace(&mut self, n: Node) -> Node {
-        let mut vals = self.cstr.iter().cloned().map(|n| n.into_node()).collect();
-        if let Node::Block(mut body, n) = n {
-            for i in 0..body.len() {
-                body[i] = super::traverse_replace(&mut vals, body[i].clone());
-            }
-            Node::Block(body, n)
-        } else {
-            unreachable!()
+    // Evaluates every term of a finite sequence up front, so later passes can't fail
+    pub fn force(&mut self) -> ArnResult<()> {
+        if !self.is_finite() {
+            return Ok(());
         }
+
+        self.resolve_length()?;
+        if let Some(len) = self.length {
+            return self.compute(len);
+        }
+        // An uncounted sequence is computed until it runs out
+        self.compute(usize::MAX)?;
+        self.length = Some(self.cstr.borrow().values.len());
+        Ok(())
     }
 
-    #[allow(clippy::unnecessary_wraps)]
-    #[inline]
-    fn _next(&mut self) -> Option<Dynamic> {
-        if self.index < self.cstr.len() {
-            self.index += 1;
-            Some(self.cstr[self.index - 1].clone())
-        } else {
-            self.index += 1;
-            let block = self.traverse_replace(self.block.clone());
-            self.env
-                .as_ref()
-                .unwrap()
-                .borrow_mut()
-                .define_var("p", Dynamic::from(self.cstr.as_slice()));
-            let res = crate::parser::parse_node(Rc::clone(self.env.as_ref().unwrap()), &block);
-            self.cstr.push(res.clone());
+    fn resolve_length(&mut self) -> ArnResult<()> {
+        if let (None, Some(len)) = (self.length, &self.unparsed_length) {
+            self.length = Some(to_u32(self.env.as_ref().unwrap(), len)? as usize);
+        }
+        Ok(())
+    }
+
+    // Makes sure the first <count> terms are cached, or every term of a derived sequence that ends sooner
+    fn compute(&mut self, count: usize) -> ArnResult<()> {
+        loop {
+            let (len, lazy) = {
+                let mut terms = self.cstr.borrow_mut();
+                if terms.values.len() >= count {
+                    return Ok(());
+                }
+                (terms.values.len(), terms.lazy.take())
+            };
+
+            let term = match lazy {
+                Some(mut lazy) => {
+                    let term = lazy.next_term();
+                    self.cstr.borrow_mut().lazy = Some(lazy);
+                    match term {
+                        Some(term) => term?,
+                        None => return Ok(()),
+                    }
+                }
+                // Only possible while the same sequence is computing a term further up the stack
+                None if self.derived => {
+                    return Err(ArnError::Type(
+                        "a sequence cannot depend on its own terms".to_owned(),
+                    ))
+                }
+                None => self.next_from_block()?,
+            };
 
-            Some(res)
+            let mut terms = self.cstr.borrow_mut();
+            // The block may have computed this term already, by reading the sequence itself
+            if terms.values.len() == len {
+                terms.values.push(term);
+            }
+        }
+    }
+
+    // Evaluates `block` with `_` replaced by the previous terms and `p` bound to all of them
+    // The block's argument is the index of the new term, so in a sequence with no starting terms
+    // `_` (or the name given to the block) is the index, which is also bound to `n`/`N` unless
+    // the program uses those names
+    fn next_from_block(&self) -> ArnResult<Dynamic> {
+        let (values, seeded) = {
+            let terms = self.cstr.borrow();
+            (terms.values.clone(), terms.seeded)
+        };
+        let block = match &self.block {
+            Node::Block(_, _) if !seeded => self.block.clone(),
+            Node::Block(body, n) => {
+                let mut vals = values
+                    .iter()
+                    .cloned()
+                    .map(Dynamic::into_node)
+                    .collect::<ArnResult<_>>()?;
+                let body = body
+                    .iter()
+                    .map(|node| super::traverse_replace(&mut vals, node.clone()))
+                    .collect::<ArnResult<_>>()?;
+                Node::Block(body, n.clone())
+            }
+            _ => unreachable!(),
+        };
+
+        // A child, so none of these leak into the scope the sequence is used in
+        let env = Rc::new(self.env.as_ref().unwrap().as_ref().clone());
+        env.borrow_mut()
+            .define_var("p", Dynamic::from(values.as_slice()));
+        // Position of the term being computed, counted from 0 and from 1
+        // Variables the program defined itself are left alone
+        for (name, index) in [("n", values.len()), ("N", values.len() + 1)] {
+            if !env.borrow().vals.contains_key(name) {
+                env.borrow_mut().define_var(name, Int::from(index));
+            }
         }
+        crate::parser::call(&env, &block, Dynamic::from(Int::from(values.len())))
+    }
+
+    #[inline]
+    fn _next(&mut self) -> ArnResult<Dynamic> {
+        self.compute(self.index + 1)?;
+        let term = self.cached(self.index).ok_or_else(|| {
+            ArnError::IndexOutOfRange(
+                "the sequence this was derived from ran out of values".to_owned(),
+            )
+        })?;
+        self.index += 1;
+
+        Ok(term)
     }
 }
 
 impl Iterator for Sequence {
-    type Item = Dynamic;
+    type Item = ArnResult<Dynamic>;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        if self.length.is_none() && self.unparsed_length.is_some() {
-            self.length = Some(to_u32(
-                self.env.as_ref().unwrap(),
-                self.unparsed_length.as_ref().unwrap(),
-            ) as usize);
+        if let Err(e) = self.resolve_length() {
+            return Some(Err(e));
         }
 
-        if self.length.is_none() {
-            self._next()
-        } else if self.index == self.length.unwrap() {
-            None
-        } else {
-            self._next()
+        match self.length {
+            Some(len) if self.index == len => None,
+            None if self.uncounted => match self.compute(self.index + 1) {
+                Err(e) => Some(Err(e)),
+                Ok(()) if self.cached(self.index).is_none() => None,
+                Ok(()) => Some(self._next()),
+            },
+            _ => Some(self._next()),
         }
     }
+
+    // Terms that are already cached are skipped without being cloned
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        if let Err(e) = self.resolve_length() {
+            return Some(Err(e));
+        }
+
+        let target = self.index.saturating_add(n);
+        if let Some(len) = self.length {
+            if target >= len {
+                self.index = len;
+                return None;
+            }
+        }
+        if let Err(e) = self.compute(target + 1) {
+            return Some(Err(e));
+        }
+
+        self.index = target.min(self.cstr.borrow().values.len());
+        self.next()
+    }
 }
 
 impl DoubleEndedIterator for Sequence {
-    fn next_back(&mut self) -> Option<Dynamic> {
-        if self.len().is_none() {
-            panic!("Can only implement DoubleEndedIterator for a finite Sequence");
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if !self.is_finite() {
+            return Some(Err(ArnError::InfiniteSequence(
+                "an infinite sequence has no last element".to_owned(),
+            )));
         }
 
-        while let Some(_) = self.next() {
-            // Build the values, the starting index is initialized
-            self.t_i = Some(self.index as isize - 1);
-        }
+        let back = if let Some(back) = self.back {
+            back
+        } else {
+            if let Err(e) = self.force() {
+                return Some(Err(e));
+            }
+            self.length.unwrap()
+        };
 
-        if self.t_i.unwrap() < 0 {
+        if back <= self.index {
+            self.back = Some(back);
             None
         } else {
-            self.t_i = Some(self.t_i.unwrap() - 1);
-            Some(self.cstr[(self.t_i.unwrap() + 1) as usize].clone())
+            self.back = Some(back - 1);
+            self.cached(back - 1).map(Ok)
+        }
+    }
+}
+
+// Values looked up by key, kept in the order their keys were first inserted
+#[derive(Clone, Debug, Default)]
+pub struct Map {
+    entries: Vec<(Dynamic, Dynamic)>,
+    // Position of every key in `entries`
+    positions: HashMap<Dynamic, usize>,
+}
+
+impl Map {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[inline]
+    pub fn get(&self, key: &Dynamic) -> Option<&Dynamic> {
+        self.positions.get(key).map(|&pos| &self.entries[pos].1)
+    }
+
+    // A key that is already present keeps its position
+    pub fn insert(&mut self, key: Dynamic, value: Dynamic) {
+        if let Some(&pos) = self.positions.get(&key) {
+            self.entries[pos].1 = value;
+        } else {
+            self.positions.insert(key.clone(), self.entries.len());
+            self.entries.push((key, value));
         }
     }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&Dynamic, &Dynamic)> {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+
+    #[inline]
+    pub fn keys(&self) -> impl Iterator<Item = &Dynamic> {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    #[inline]
+    pub fn values(&self) -> impl Iterator<Item = &Dynamic> {
+        self.entries.iter().map(|(_, v)| v)
+    }
+}
+
+impl FromIterator<(Dynamic, Dynamic)> for Map {
+    fn from_iter<T: IntoIterator<Item = (Dynamic, Dynamic)>>(iter: T) -> Self {
+        let mut map = Self::new();
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+impl PartialEq for Map {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+// Entries are hashed on their own and summed, so the order of insertion doesn't matter
+impl Hash for Map {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.entries
+            .iter()
+            .map(|entry| {
+                let mut hasher = DefaultHasher::new();
+                entry.hash(&mut hasher);
+                hasher.finish()
+            })
+            .fold(0_u64, u64::wrapping_add)
+            .hash(state);
+    }
 }
@@ -0,0 +1,106 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn arrays_compare_element_wise() {
+    for (program, expected) in [
+        ("[1 2]=[1 2]", "1"),
+        ("[1 2]=[1 3]", "0"),
+        ("[1 2]=[1 2 3]", "0"),
+        ("[\"1\" 2]=[1 \"2\"]", "1"),
+        ("[[1 2] [3]]=[[1 2] [3]]", "1"),
+        ("[1 2]=sE", "0"),
+        ("sE=sE", "0"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn nested_arrays_can_be_searched_and_grouped() {
+    assert_eq!(run("[[1 2] [3]]:i[3]"), "1");
+    assert_eq!(run("#>[[1 2] [3] [1 2]]"), "1 2 \n3 \n");
+    assert_eq!(run("[[1 2] [3] [1 2]]:@"), "1 2  1 2  \n3  \n");
+}
+
+#[test]
+fn nested_arrays_sort_by_length_then_element_wise() {
+    for (program, expected) in [
+        (":<[[2 1] [1 5] [1]]", "1 \n1 5 \n2 1 \n"),
+        (":>[[2 1] [1 5] [1]]", "2 1 \n1 5 \n1 \n"),
+        (":<[[1 2 3] [9] [5 1]]", "9 \n5 1 \n1 2 3 \n"),
+        ("[[1 2 3] [9] [5 1]].ma", "1\n2\n3\n"),
+        ("[[1 2 3] [9] [5 1]].mi", "9\n"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn equal_values_are_deduplicated_together() {
+    for (program, expected) in [
+        ("#>[1 \"1\" (2/2) (3/2) _1 _2]", "1\n1.5\n_1\n_2\n"),
+        ("#>[4294967296 4294967297]", "4294967296\n4294967297\n"),
+        ("#:[1 2 \"2\" (4/2) 3]", "1\n1\n0\n0\n1\n"),
+        ("[1 2 \"1\" 3 2]:@", "1 1 \n2 2 \n3 \n"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn mixed_values_sort_by_kind() {
+    for (program, expected) in [
+        (":<[1 \"a\" 2]", "1\n2\na\n"),
+        (":>[1 \"a\" 2]", "a\n2\n1\n"),
+        (":<[[1 \"a\"] [1 2] [\"b\"]]", "b \n1 2 \n1 a \n"),
+        (":<[[1] 2 \"x\" 1]", "1\n2\nx\n1 \n"),
+        // Numeric strings are ordered as numbers, even against each other
+        (":<[\"10\" \"9\" (19/2)]", "9\n9.5\n10\n"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn many_mixed_values_sort() {
+    // Numeric strings, fractions and booleans, enough that an inconsistent order would be noticed
+    let mut values = Vec::new();
+    let mut halves = Vec::new();
+    for i in (0..100).map(|i| i * 37 % 100) {
+        values.push(format!("\"{i}\""));
+        values.push(format!("({}/2)", 2 * i + 3));
+        halves.extend([2 * i, 2 * i + 3]);
+        if i % 10 == 0 {
+            values.push(format!("(1={})", i % 20 / 10));
+            halves.push(2 * (i % 20 / 10));
+        }
+    }
+    halves.sort_unstable();
+    let expected = halves
+        .iter()
+        .map(|half| {
+            if half % 2 == 0 {
+                format!("{}\n", half / 2)
+            } else {
+                format!("{}.5\n", half / 2)
+            }
+        })
+        .collect::<String>();
+
+    let program = format!(":<[{}]", values.join(" "));
+    assert_eq!(run(&program), expected);
+}
+
+#[test]
+fn booleans_compare_as_0_and_1() {
+    for (program, expected) in [
+        ("(1=1)<2", "1"),
+        ("(1=1)>0", "1"),
+        ("(1=1)<1", "0"),
+        ("(0=1)<1", "1"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
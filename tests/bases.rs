@@ -0,0 +1,89 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn any_base_and_size() {
+    for (program, expected) in [
+        ("10;b", "1010"),
+        ("255;16", "ff"),
+        ("\"FF\";H", "255"),
+        ("\"zz\";f36", "1295"),
+        ("10;b16", "3f2"),
+        ("_10;b", "_1010"),
+        ("\"_1010\";B", "_10"),
+        (
+            "123456789012345678901234567890123456789012345;36",
+            "39urvue1w6yr4x36itwjzgiroejqx",
+        ),
+        (
+            "\"39urvue1w6yr4x36itwjzgiroejqx\";f36",
+            "123456789012345678901234567890123456789012345",
+        ),
+        ("[1 2 3];b", "01\n10\n11\n"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn digit_lists_and_alphabets() {
+    for (program, expected) in [
+        ("1234;d", "1\n2\n3\n4\n"),
+        ("255;d16", "15\n15\n"),
+        ("[1 2 3 4];D", "1234"),
+        ("\"101\";D2", "5"),
+        ("[[1 0] [1 1]];D2", "2\n3\n"),
+        ("10;\"a.-\"", "-.-."),
+        ("\"-.-.\";\"A.-\"", "10"),
+        ("255;\"a0123456789ABCDEF\"", "FF"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn bijective_base() {
+    for (program, expected) in [
+        ("26;j26", "q"),
+        ("27;j26", "11"),
+        ("10;j10", "a"),
+        ("\"11\";J26", "27"),
+        ("3;j1", "111"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn bad_conversions_are_errors() {
+    for (program, expected) in [
+        ("5;37", "error: Type error: base 37 is not between 2 and 36"),
+        (
+            "5;j",
+            "error: Type error: `j` needs to be followed by a base",
+        ),
+        (
+            "5;x",
+            "error: Type error: unrecognized base conversion character `x`",
+        ),
+        (
+            "\"12\";B",
+            "error: Type error: `12` is not a valid base 2 number",
+        ),
+        (
+            "\"19\";D8",
+            "error: Type error: `9` is not a digit in base 8",
+        ),
+        (
+            "10;\"a00\"",
+            "error: Type error: the alphabet `00` repeats a digit",
+        ),
+        (
+            "_3;j2",
+            "error: Type error: `_3` cannot be written in bijective base 2",
+        ),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
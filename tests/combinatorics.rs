@@ -0,0 +1,105 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn counting() {
+    for (program, expected) in [
+        ("5:B2", "10"),
+        ("5:P2", "20"),
+        ("100:B50", "100891344545564193334812497256"),
+        ("(10^20):P2", "9999999999999999999900000000000000000000"),
+        ("_3:B2", "6"),
+        ("4:B_1", "0"),
+        ("3:B5", "0"),
+        ("4:P0", "1"),
+        (
+            "4:B(2^40)",
+            "error: Type error: `:B` cannot pick `1099511627776` values",
+        ),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn permutations_and_combinations() {
+    for (program, expected) in [
+        (
+            "[1 2 3].prm",
+            "1 2 3 \n1 3 2 \n2 1 3 \n2 3 1 \n3 1 2 \n3 2 1 \n",
+        ),
+        ("[1 2 3]:P2", "1 2 \n1 3 \n2 1 \n2 3 \n3 1 \n3 2 \n"),
+        ("[1 2 3 4]:B2", "1 2 \n1 3 \n1 4 \n2 3 \n2 4 \n3 4 \n"),
+        ("\"abc\":B2", "a b \na c \nb c \n"),
+        ("([1 2]:P3)#", "0"),
+        ("(1=>8).prm#", "40320"),
+        ("((1=>10):B4)#", "210"),
+        // Far too many to list, but the first ones are still there
+        ("(1=>30).prm?1?_1", "29"),
+        ("((1=>20).prm?<2)?1?_1", "19"),
+        (
+            "sP:B2",
+            "error: Infinite sequence: `:B` cannot be used on an infinite sequence",
+        ),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn products_and_subsets() {
+    for (program, expected) in [
+        ("[[1 2] [3 4]].crt", "1 3 \n1 4 \n2 3 \n2 4 \n"),
+        ("[[1 2] [3] [5 6]].crt", "1 3 5 \n1 3 6 \n2 3 5 \n2 3 6 \n"),
+        ("[[1 2] a].crt#", "0"),
+        ("[1 2 3].pst", "\n1 \n2 \n3 \n1 2 \n1 3 \n2 3 \n1 2 3 \n"),
+        // Smallest first, each subset keeping the order of the list
+        ("[3 1 2].pst", "\n3 \n1 \n2 \n3 1 \n3 2 \n1 2 \n3 1 2 \n"),
+        ("[2 2 1].pst", "\n2 \n2 \n1 \n2 2 \n2 1 \n2 1 \n2 2 1 \n"),
+        (
+            "([\"10\" 9 \"a\" (19/2)].pst)?>10",
+            "a 9.5 \n10 9 a \n10 9 9.5 \n10 a 9.5 \n9 a 9.5 \n10 9 a 9.5 \n",
+        ),
+        ("(1=>16).pst#", "65536"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn too_many_to_count() {
+    for (program, expected) in [
+        (
+            "(1=>30).prm?<1",
+            "1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 \n",
+        ),
+        ("((1=>70).pst?>1)?<2", "1 \n2 \n"),
+        ("(((1=>30).prm?%2)?<3)#", "3"),
+        ("(500.ptn?<1)?0", "500\n"),
+        (
+            "(1=>30).prm#",
+            "error: Type error: `#` cannot be used on a sequence with too many terms to count",
+        ),
+        (
+            "(1=>30).prm?_1",
+            "error: Type error: `?` cannot be used on a sequence with too many terms to count",
+        ),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn partitions() {
+    for (program, expected) in [
+        ("4.ptn", "4 \n3 1 \n2 2 \n2 1 1 \n1 1 1 1 \n"),
+        ("10.ptn#", "42"),
+        ("100.ptn#", "190569292"),
+        ("0.ptn#", "1"),
+        ("1000.ptn?<2", "1000 \n999 1 \n"),
+        ("_1.ptn", "error: Type error: `_1` has no partitions"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
@@ -0,0 +1,35 @@
+// Helpers shared by the integration tests, each test file only uses some of them
+#![allow(dead_code)]
+
+use arn_language::{Interpreter, Options, RationalMode};
+
+// Evaluates <program>, giving its result or the error it stopped with
+pub fn run_with(program: &str, options: Options) -> String {
+    Interpreter::new(options)
+        .and_then(|mut arn| arn.eval(program).map(|result| arn.format(&result)))
+        .unwrap_or_else(|e| format!("error: {e}"))
+}
+
+pub fn run(program: &str) -> String {
+    run_with(program, Options::default())
+}
+
+pub fn run_in(program: &str, rational: RationalMode) -> String {
+    run_with(
+        program,
+        Options {
+            rational,
+            ..Options::default()
+        },
+    )
+}
+
+pub fn run_with_stdin(program: &str, stdin: &str) -> String {
+    run_with(
+        program,
+        Options {
+            stdin: stdin.to_owned(),
+            ..Options::default()
+        },
+    )
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn imaginary_literals() {
+    for (program, expected) in [
+        ("2i", "2i"),
+        ("_2i", "_2i"),
+        ("3+4i", "3+4i"),
+        ("3-4i", "3-4i"),
+        ("1i*1i", "_1"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn complex_arithmetic() {
+    for (program, expected) in [
+        ("(3+2i)*(1-1i)", "5-1i"),
+        ("(1+1i)/(1-1i)", "1i"),
+        ("(1+1i)^2", "2i"),
+        ("(2+3i)-3i", "2"),
+        (":-3i", "1.5i"),
+        (":*(1+1i)", "2i"),
+        ("(3+4i).|", "5"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn square_roots_of_negatives_are_imaginary() {
+    assert_eq!(run(":/_4"), "2i");
+    assert_eq!(run(":/_2"), "1.414i");
+    assert_eq!(run(":/(_3+4i)"), "1+2i");
+}
+
+#[test]
+fn complex_numbers_compare_by_equality_only() {
+    assert_eq!(run("(1+2i)=(1+2i)"), "1");
+    assert_eq!(run("(1+2i)=1"), "0");
+    assert_eq!(run("(1+2i)<(3+4i)"), "0");
+    assert_eq!(run("(1i):A"), "1.571");
+}
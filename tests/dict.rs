@@ -0,0 +1,54 @@
+use arn_language::utils::dict::{compress, decompress};
+use arn_language::{Interpreter, Options};
+
+// Decompresses a literal produced by `compress`, delimiters included
+fn round_trip(text: &str) -> String {
+    let literal = compress(text).unwrap();
+    let delim = literal.chars().next().unwrap();
+    let inner = &literal[1..literal.len() - 1];
+
+    assert!(!inner.contains(delim), "{} contains its delimiter", literal);
+    decompress(inner, delim == '\'')
+}
+
+#[test]
+fn round_trips() {
+    for text in [
+        "Hello, World!",
+        "The quick brown fox",
+        "I am not a number",
+        "Fizz",
+        "Buzz",
+        "This Is Title Case",
+        "The end.",
+        "",
+    ] {
+        assert_eq!(round_trip(text), text);
+    }
+}
+
+#[test]
+fn shortest_encoding() {
+    // "the" and "of" are the first two words in the dictionary
+    assert_eq!(compress("The").unwrap(), "`aa`");
+    assert_eq!(compress("The of").unwrap(), "`a ab`");
+    // A word at a multiple of 100 only needs one char before a literal
+    assert_eq!(compress("The!").unwrap(), "`a!`");
+}
+
+#[test]
+fn uncompressible() {
+    assert!(compress("qzxv").is_err());
+    // The first word is always capitalized
+    assert!(compress("hello world").is_err());
+    assert!(compress(" padded ").is_err());
+}
+
+#[test]
+fn evaluates_as_literal() {
+    let mut arn = Interpreter::new(Options::default()).unwrap();
+    for text in ["Hello, World!", "The Quick Brown Fox", "The end."] {
+        let literal = compress(text).unwrap();
+        assert_eq!(arn.eval(&literal).unwrap().to_string(), text);
+    }
+}
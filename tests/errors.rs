@@ -0,0 +1,49 @@
+mod common;
+
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use common::run;
+
+#[test]
+fn bad_programs_are_errors() {
+    for (program, expected) in [
+        ("x+1", "error: Unrecognized value `x`"),
+        ("[1 2]?5", "error: Index out of range: no value at index 5"),
+        (
+            "sE#",
+            "error: Infinite sequence: `#` cannot be used on an infinite sequence",
+        ),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn reversed_ranges_are_empty() {
+    for program in ["5=>2", "5->2", "99999999999999999999=>1", "3->3"] {
+        assert_eq!(run(&format!("({program})#")), "0", "{}", program);
+    }
+}
+
+#[test]
+fn bad_command_lines_are_errors() {
+    let program = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/hello_world.arn");
+    let program = program.to_str().unwrap();
+    for args in [
+        vec!["-p", "abc", program],
+        vec!["-p", "0", program],
+        vec!["-o", "abc", program],
+        vec!["--stack", "0", program],
+        vec!["no/such/file.arn"],
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_arn"))
+            .args(&args)
+            .stdin(Stdio::null())
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(1), "{:?}: {}", args, stderr);
+        assert!(!stderr.contains("panicked"), "{:?}: {}", args, stderr);
+    }
+}
@@ -0,0 +1,82 @@
+// Runs every program in `examples/` through the `arn` binary and compares its STDOUT
+// against `tests/golden/<name>.stdout`. Optional `<name>.flags` (command line flags,
+// whitespace separated) and `<name>.stdin` files sit next to it.
+// Set `ARN_BLESS=1` to rewrite the expected output after an intended change.
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+fn read_optional(path: &Path) -> String {
+    fs::read_to_string(path).unwrap_or_default()
+}
+
+fn run_example(program: &Path, golden: &Path) -> String {
+    let flags = read_optional(&golden.with_extension("flags"));
+    let stdin = read_optional(&golden.with_extension("stdin"));
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_arn"))
+        .args(flags.split_whitespace())
+        .arg(program)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "{} failed:\n{}",
+        program.display(),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn examples_match_golden_output() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let bless = std::env::var_os("ARN_BLESS").is_some();
+    let mut failures = Vec::new();
+
+    let mut programs = fs::read_dir(root.join("examples"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "arn"))
+        .collect::<Vec<_>>();
+    programs.sort();
+    assert!(!programs.is_empty(), "no examples found");
+
+    for program in programs {
+        let name = program.file_stem().unwrap();
+        let golden = root
+            .join("tests/golden")
+            .join(name)
+            .with_extension("stdout");
+        let actual = run_example(&program, &golden);
+
+        if bless {
+            fs::write(&golden, &actual).unwrap();
+        } else {
+            match fs::read_to_string(&golden) {
+                Ok(expected) if expected == actual => {}
+                Ok(expected) => failures.push(format!(
+                    "{}:\n--- expected\n{expected}\n--- actual\n{actual}",
+                    program.display()
+                )),
+                Err(_) => {
+                    failures.push(format!("{} has no {}", program.display(), golden.display()))
+                }
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::{run, run_with_stdin};
+
+#[test]
+fn integer_operations_stay_exact() {
+    for (program, expected) in [
+        ("2^100", "1267650600228229401496703205376"),
+        ("20.f", "2432902008176640000"),
+        ("sF?100", "573147844013817084101"),
+        (
+            "123456789012345678901234567890+1",
+            "123456789012345678901234567891",
+        ),
+        (
+            "99999999999999999999*99999999999999999999",
+            "9999999999999999999800000000000000000001",
+        ),
+        ("3e20-1", "299999999999999999999"),
+        ("_7%3", "_1"),
+        ("84/4", "21"),
+        (":/144", "12"),
+        (":-10", "5"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn large_factorials_and_folds() {
+    let factorial = rug::Integer::from(rug::Integer::factorial(300)).to_string();
+    assert_eq!(run("300.f"), factorial);
+    assert_eq!(run("*\\(1=>300)"), factorial);
+    assert_eq!(run("+\\(1=>2000)"), "2001000");
+    assert_eq!(run("_3.f"), "1");
+    assert_eq!(
+        run("(2^40).f"),
+        "error: Type error: `f` cannot take the factorial of `1099511627776`"
+    );
+}
+
+#[test]
+fn promotes_to_float_when_needed() {
+    for (program, expected) in [
+        ("7/2", "3.5"),
+        (":-5", "2.5"),
+        (":/2", "1.414"),
+        ("1e_2", "1.000e_2"),
+        ("(7/2)*2", "7"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn input_is_read_exactly() {
+    assert_eq!(
+        run_with_stdin("_+1", "99999999999999999999999"),
+        "100000000000000000000000"
+    );
+}
+
+#[test]
+fn integers_compare_with_floats() {
+    for (program, expected) in [
+        ("(6/2)=3", "1"),
+        ("(5/2)<3", "1"),
+        ("(5/2)>2", "1"),
+        ("100000000000000000001>100000000000000000000", "1"),
+        ("\"12\"=12", "1"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
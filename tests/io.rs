@@ -0,0 +1,70 @@
+use arn_language::utils::io::SharedBuffer;
+use arn_language::{Interpreter, Options};
+
+fn interpreter(input: &'static str, output: &SharedBuffer) -> Interpreter {
+    Interpreter::with_io(Options::default(), input, output.clone()).unwrap()
+}
+
+#[test]
+fn reads_from_given_input() {
+    let output = SharedBuffer::new();
+    let mut arn = interpreter("5", &output);
+
+    assert_eq!(arn.eval("+\\~").unwrap().to_string(), "15");
+    assert_eq!(output.contents(), "");
+}
+
+#[test]
+fn builtins_write_to_output() {
+    let output = SharedBuffer::new();
+    let mut arn = interpreter("", &output);
+
+    arn.eval("3.ol").unwrap();
+    arn.eval("\"a\".o").unwrap();
+    arn.eval("\"b\".o").unwrap();
+    assert_eq!(output.contents(), "3\nab");
+}
+
+#[test]
+fn prints_results() {
+    let output = SharedBuffer::new();
+    let mut arn = interpreter("", &output);
+
+    let result = arn.eval("[1 2]z[3 4]").unwrap();
+    arn.print(&result).unwrap();
+    assert_eq!(output.contents(), "1 3 \n2 4 \n\n");
+}
+
+#[test]
+fn stops_infinite_output_at_first_error() {
+    let output = SharedBuffer::new();
+    let mut arn = interpreter("", &output);
+
+    // The fourth term indexes past the end of the previous terms
+    let result = arn.eval("[1 2 3 {?5}]").unwrap();
+    assert!(arn.print(&result).is_err());
+    assert_eq!(output.contents(), "1\n2\n3\n");
+}
+
+#[test]
+fn builtins_stream_infinite_sequences() {
+    let output = SharedBuffer::new();
+    let mut arn = interpreter("", &output);
+
+    assert!(arn.eval("[1 2 3 {?5}].ol").is_err());
+    assert_eq!(output.contents(), "1\n2\n3\n");
+    arn.eval("(sE?<2).ol").unwrap();
+    assert_eq!(output.contents(), "1\n2\n3\n2\n4\n\n");
+}
+
+#[test]
+fn reset_keeps_io() {
+    let output = SharedBuffer::new();
+    let mut arn = interpreter("7", &output);
+
+    arn.eval("x:=1").unwrap();
+    arn.reset().unwrap();
+    assert!(arn.eval("x").is_err());
+    arn.eval("_.ol").unwrap();
+    assert_eq!(output.contents(), "7\n");
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn literals_keep_insertion_order() {
+    assert_eq!(run(".[\"b\" 1 \"a\" 2]"), "b: 1\na: 2\n");
+    assert_eq!(run(".[1 2 1 3]"), "1: 3\n");
+    assert_eq!(run(".[]"), "");
+    assert_eq!(
+        run(".[1]"),
+        "error: Type error: every key in a map literal needs a value"
+    );
+}
+
+#[test]
+fn keys_are_looked_up_by_value() {
+    for (program, expected) in [
+        (".[1 \"a\"]?\"1\"", "a"),
+        (".[[1 2] \"a\"]?[1 2]", "a"),
+        (".[1 2].+1<>5?1", "5"),
+        ("[\"x\" \"y\" \"x\"]:c?\"x\"", "2"),
+        (
+            ".[1 2]?3",
+            "error: Index out of range: no value for key `3`",
+        ),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn maps_compare_regardless_of_order() {
+    for (program, expected) in [
+        (".[1 2 3 4]=.[3 4 1 2]", "1"),
+        (".[1 2 3 4]=.[1 2]", "0"),
+        (".[1 2]=[1 2]", "0"),
+        ("(#>[.[1 2 3 4] .[3 4 1 2]])#", "1"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn arrays_are_keyed_by_index() {
+    assert_eq!(run("[\"a\" \"b\"]:k"), "0\n1\n");
+    assert_eq!(run("[\"a\" \"b\"].+1<>\"c\""), "0: a\n1: c\n");
+    assert_eq!(
+        run("sE:k"),
+        "error: Infinite sequence: an infinite sequence cannot be used as a map"
+    );
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use arn_language::RationalMode;
+use common::{run, run_in};
+
+#[test]
+fn products_and_powers() {
+    for (program, expected) in [
+        ("[[1 2] [3 4]].*[[5 6] [7 8]]", "19 22 \n43 50 \n"),
+        ("[[1 2 3]].*[[1] [2] [3]]", "14 \n"),
+        ("[[1 1] [1 0]].^10", "89 55 \n55 34 \n"),
+        ("[[1 1] [1 0]].^0", "1 0 \n0 1 \n"),
+        ("[1 2 3]:.[4 5 6]", "32"),
+        ("3.idm", "1 0 0 \n0 1 0 \n0 0 1 \n"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn determinants_stay_exact() {
+    for (program, expected) in [
+        ("[[1 2] [3 4]].det", "_2"),
+        ("[[0 1] [1 0]].det", "_1"),
+        ("[[2 0 1] [1 3 2] [1 1 1]].det", "0"),
+        ("[[6 1 1] [4 _2 5] [2 8 7]].det", "_306"),
+        ("a.det", "1"),
+        // Entries are tested for 0 by value, so "0" is never a pivot
+        ("[[\"0\" 1] [1 0]].det", "_1"),
+        ("[[\"0\" \"0\"] [1 2]].det", "0"),
+        ("[[(1=1) 0] [0 (1=1)]].det", "1"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn inverses() {
+    assert_eq!(run("[[2 1] [1 1]].inv"), "1 _1 \n_1 2 \n");
+    assert_eq!(
+        run_in("[[1 2] [3 4]].inv", RationalMode::Fraction),
+        "_2 1 \n3/2 _1/2 \n"
+    );
+    assert_eq!(
+        run_in("([[4 7] [2 6]].inv).*[[4 7] [2 6]]", RationalMode::Fraction),
+        run("2.idm")
+    );
+}
+
+#[test]
+fn negative_powers_invert() {
+    assert_eq!(
+        run_in("[[1 2] [3 4]].^_1", RationalMode::Fraction),
+        "_2 1 \n3/2 _1/2 \n"
+    );
+    assert_eq!(
+        run_in("[[2 0] [0 4]].^_2", RationalMode::Fraction),
+        "1/4 0 \n0 1/16 \n"
+    );
+    assert_eq!(
+        run("[[1 2] [2 4]].^_1"),
+        "error: Type error: `.^` cannot invert a singular matrix"
+    );
+}
+
+#[test]
+fn bad_shapes_are_errors() {
+    for (program, expected) in [
+        (
+            "[[1 2] [3]].det",
+            "error: Type error: `det` cannot be used on a ragged matrix, row 2 has length 1 instead of 2",
+        ),
+        (
+            "[[1 2 3] [4 5 6]].inv",
+            "error: Type error: `inv` needs a square matrix, not a 2x3 one",
+        ),
+        (
+            "[[1 2]].*[[1 2]]",
+            "error: Type error: `.*` cannot multiply a 1x2 matrix by a 1x2 one",
+        ),
+        (
+            "[[1 2] [2 4]].inv",
+            "error: Type error: `inv` cannot invert a singular matrix",
+        ),
+        (
+            "[[\"a\" 1] [1 0]].inv",
+            "error: Type error: `inv` needs a matrix of numbers, but row 1 contains `a`",
+        ),
+        (
+            "[1 2]:.[1]",
+            "error: Type error: `:.` needs vectors of the same length, not 2 and 1",
+        ),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn factorization_of_big_numbers() {
+    for (program, expected) in [
+        ("12.pf", "2\n2\n3\n"),
+        ("_12.pf", "2\n2\n3\n"),
+        ("(2^64+1).pf", "274177\n67280421310721\n"),
+        ("((10^9+7)^2).pf", "1000000007\n1000000007\n"),
+        ("360.pf:c", "2: 3\n3: 2\n5: 1\n"),
+        ("0.pf", "error: Type error: `0` has no prime factorization"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn primes() {
+    for (program, expected) in [
+        ("(2^89-1).isp", "1"),
+        ("(2^89+1).isp", "0"),
+        ("1.isp", "0"),
+        ("10000.pn", "104729"),
+        ("(10^30).nxp", "1000000000000000000000000000057"),
+        ("10.pvp", "7"),
+        ("2.pvp", "error: Type error: there is no prime below `2`"),
+        ("0.pn", "error: Type error: there is no 0th prime"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn arithmetic_functions() {
+    for (program, expected) in [
+        ("[12 18 30].gcd", "6"),
+        ("[4 6 10].lcm", "60"),
+        ("[(2^100) (6^50)].gcd", "1125899906842624"),
+        ("36.tot", "12"),
+        ("1.tot", "1"),
+        ("12.sig", "28"),
+        ("(2^40).sig", "2199023255551"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn factors_of_any_size() {
+    assert_eq!(run("*.0"), "");
+    assert_eq!(run("*.(2^61-1)"), "2305843009213693951\n1\n");
+    assert_eq!(run("#(*.(2^30*3^20))"), "651");
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use arn_language::utils::consts::OPTIONS;
+use common::run;
+
+// Operator, program using it, expected output (trailing whitespace ignored)
+const CASES: &[(&str, &str, &str)] = &[
+    (".", "f:={+1}\n3.f", "4"),
+    (".", "[1 5 2].ma", "5"),
+    ("^", "2^10", "1024"),
+    ("^", "\"ab\"^3", "ababab"),
+    ("<>", "1<>2", "1\n2"),
+    ("*", "6*7", "42"),
+    (".*", "[[1 2] [3 4]].*[[5 6] [7 8]]", "19 22 \n43 50"),
+    (".^", "[[1 1] [1 0]].^5", "8 5 \n5 3"),
+    (":.", "[1 2 3]:.[4 5 6]", "32"),
+    ("/", "7/2", "3.5"),
+    ("%", "7%3", "1"),
+    (":|", "[1 2 3]:|\"-\"", "1-2-3"),
+    (":!", "\"a,b,c\":!\",\"", "a\nb\nc"),
+    ("+", "1+2", "3"),
+    ("-", "5-8", "_3"),
+    (".$", "[1 2 3 4].$1", "1 \n2 3 4"),
+    (".+", ".[1 2].+3<>4", "1: 2\n3: 4"),
+    (".~", "4.~", "4\n3\n2\n1"),
+    ("=>", "2=>5", "2\n3\n4\n5"),
+    ("->", "2->5", "2\n3\n4"),
+    ("~", "~4", "1\n2\n3\n4"),
+    ("#", "[1 2 3]#", "3"),
+    (";", "10;b", "1010"),
+    (";", "\"1010\";B", "10"),
+    (":_", "[[1 2] [3 4]]:_", "1\n2\n3\n4"),
+    (":%", "[[1 2] [3 4]]:%", "1 3 \n2 4"),
+    (".|", "(0-5).|", "5"),
+    (":R", "(3+4i):R", "3"),
+    (":I", "(3+4i):I", "4"),
+    (":C", "(3+4i):C", "3-4i"),
+    (":A", "_1:A", "3.142"),
+    (".<", "[1 2 3].<", "3\n2\n1"),
+    ("..", "[2 5]..", "2\n3\n4"),
+    (".=", "[2 5].=", "2\n3\n4\n5"),
+    (":n", "\"a\nb\":n", "a\nb"),
+    (":s", "\"a b\":s", "a\nb"),
+    (":}", "[1 2 3]:}", "3"),
+    (":{", "[1 2 3]:{", "1"),
+    (".}", "[1 2 3].}", "1\n2"),
+    (".{", "[1 2 3].{", "2\n3"),
+    (":@", "[1 2 1 3 2]:@", "1 1 \n2 2 \n3"),
+    (":c", "[1 2 1 3 2]:c", "1: 2\n2: 2\n3: 1"),
+    (":k", ".[\"a\" 1 \"b\" 2]:k", "a\nb"),
+    (":V", ".[\"a\" 1 \"b\" 2]:V", "1\n2"),
+    ("^*", "16^*", "1"),
+    ("^*", "15^*", "0"),
+    ("&.", "&.{+2}1 3", "7"),
+    ("^%", "^%3 4 5", "1"),
+    (":i", "[4 5 6]:i5", "1"),
+    (":i", "[4 5 6]:i7", "_1"),
+    (":p", "(8:p(1/3))=(1/3)", "0"),
+    (":p", "200:p(:/2)", "1.414"),
+    (":P", "10:P3", "720"),
+    (":P", "([1 2 3]:P2)#", "6"),
+    (":B", "10:B3", "120"),
+    (":B", "[1 2 3]:B2", "1 2 \n1 3 \n2 3"),
+    ("!", "!0", "1"),
+    (":v", ":v(5/2)", "2"),
+    (":v", ":v\"ABC\"", "abc"),
+    (":^", ":^(5/2)", "3"),
+    (":^", ":^\"abc\"", "ABC"),
+    ("++", "++4", "5"),
+    ("--", "--4", "3"),
+    (":*", ":*5", "25"),
+    (":/", ":/16", "4"),
+    (":+", ":+5", "10"),
+    (":-", ":-5", "2.5"),
+    (":>", ":>[3 1 2]", "3\n2\n1"),
+    (":<", ":<[3 1 2]", "1\n2\n3"),
+    ("|:", "|:\"abcd\"", "ab\ndc"),
+    ("?.", "?.[7]", "7"),
+    ("#.", "#.20", "2\n3\n5\n7\n11\n13\n17\n19"),
+    ("*.", "*.12", "12\n2\n3\n4\n6\n1"),
+    ("$.", "$.[1 2 3 4]", "1 2 \n3 4"),
+    ("!.", "!.\"1+2\"", "3"),
+    ("z", "[1 2]z[3 4]", "1 3 \n2 4"),
+    ("#>", "#>[1 2 1 3]", "1\n2\n3"),
+    ("#:", "#:[1 2 1 3]", "1\n1\n0\n1"),
+    ("?", "[4 5 6]?1", "5"),
+    ("?", ".[\"a\" 5]?\"a\"", "5"),
+    ("?", "[4 5 6]?_1", "6"),
+    ("?<", "\"abcde\"?<_2", "abc"),
+    ("?>", "[1 2 3 4]?>2", "3\n4"),
+    ("?%", "[1 2 3 4 5]?%2", "1\n3\n5"),
+    (":?", "\"abcde\":?[3 0 _1]", "dcb"),
+    (":#", "sO:#3", "1\n3\n5"),
+    ("|", "[1 2]|3", "1\n2\n3"),
+    ("|", "\"ab\"|\"cd\"", "abcd"),
+    ("=", "1=1", "1"),
+    ("!=", "1!=2", "1"),
+    ("<", "1<2", "1"),
+    ("<=", "2<=2", "1"),
+    (">", "1>2", "0"),
+    (">=", "1>=2", "0"),
+    ("&&", "1&&2", "2"),
+    ("&&", "0&&2", "0"),
+    ("||", "0||3", "3"),
+    (":", "3&({*2}: {<20})", "24"),
+    ("::", "[1 1 2 2]::=", "1 1 \n2 2"),
+    ("??", "3??1{*2}", "6"),
+    ("??", "3??0{*2}", "3"),
+    ("@", "[1 2 3]@{*2}", "2\n4\n6"),
+    (":g", "[1 2 3 4]:g{%2}", "1: 1 3 \n0: 2 4"),
+    ("&", "3&{+1}", "4"),
+    ("$", "${%2}[1 2 3 4]", "1\n3"),
+    ("$:", "$:{>3}[1 2 3 4]", "1"),
+    (":$", ":${<3}[1 2 3 1]", "1\n2"),
+    ("/:", "/:{%2}[1 2 3 4 5]", "3"),
+    ("\\", "+\\[1 2 3]", "6"),
+    (":\\", "+:\\[1 2 3]", "1\n3\n6"),
+    (":=", "f:={*3}\n2.f", "6"),
+];
+
+#[test]
+fn every_operator_has_a_case() {
+    let missing = OPTIONS
+        .operators
+        .iter()
+        .filter(|op| !CASES.iter().any(|(case, ..)| case == op))
+        .collect::<Vec<_>>();
+
+    assert!(missing.is_empty(), "no test case for {:?}", missing);
+}
+
+#[test]
+fn operators() {
+    let failures = CASES
+        .iter()
+        .filter_map(|(op, program, expected)| {
+            let actual = run(program);
+            if actual.trim_end() == *expected {
+                None
+            } else {
+                Some(format!(
+                    "`{op}`: {program:?}\n  expected {expected:?}\n  got      {actual:?}"
+                ))
+            }
+        })
+        .collect::<Vec<_>>();
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use arn_language::RationalMode;
+use common::{run, run_in};
+
+#[test]
+fn any_exponent() {
+    for (program, expected) in [
+        ("5^0", "1"),
+        ("0^0", "1"),
+        ("2^_1", "5.000e_1"),
+        ("2^(1/2)", "1.414"),
+        ("_2^3", "_8"),
+        ("(2/3)^_2", "2.25"),
+        ("0^_1", "inf"),
+        ("\"ab\"^3", "ababab"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn exact_roots_stay_exact() {
+    assert_eq!(run("4^(1/2)"), "2");
+    assert_eq!(run("8^(2/3)"), "4");
+    assert_eq!(run_in("2^_3", RationalMode::Fraction), "1/8");
+    assert_eq!(run_in("(4/9)^(_1/2)", RationalMode::Fraction), "3/2");
+}
+
+#[test]
+fn negative_roots_are_complex() {
+    assert_eq!(run("_4^(1/2)"), "2i");
+    assert_eq!(run("(1i)^2"), "_1");
+}
+
+#[test]
+fn huge_integer_powers() {
+    assert_eq!(run("#(3^100000)"), "47713");
+    assert_eq!(run("^%(2^100) (3^100) 1000000007"), "559191699");
+}
+
+#[test]
+fn modular_exponentiation() {
+    for (program, expected) in [
+        ("^%2 10 1000", "24"),
+        ("^%3 _1 7", "5"),
+        ("^%_2 3 5", "2"),
+        (
+            "^%2 _1 4",
+            "error: Type error: `2` has no inverse modulo `4`",
+        ),
+        ("^%2 3 0", "error: Type error: `^%` cannot reduce modulo 0"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
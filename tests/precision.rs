@@ -0,0 +1,105 @@
+use arn_language::{Interpreter, Options, Precision};
+
+fn interpreter(float_precision: u32, output_precision: usize) -> Interpreter {
+    Interpreter::new(Options {
+        float_precision,
+        output_precision,
+        ..Options::default()
+    })
+    .unwrap()
+}
+
+#[test]
+fn interpreters_keep_their_own_precision() {
+    let mut low = interpreter(8, 4);
+    let mut high = interpreter(200, 30);
+
+    let a = low.eval("1/3").unwrap();
+    let b = high.eval("1/3").unwrap();
+    assert_eq!(high.format(&b), "3.33333333333333333333333333333e_1");
+    let c = low.eval("1/3").unwrap();
+    assert_eq!(low.format(&c), "3.340e_1");
+    assert_ne!(a, b);
+}
+
+#[test]
+fn values_do_not_pick_up_the_precision_of_later_interpreters() {
+    let mut low = interpreter(8, 4);
+    let mut high = interpreter(200, 30);
+
+    let a = low.eval("1/3").unwrap();
+    let before = a.to_string();
+    let b = high.eval("1/3").unwrap();
+    assert_eq!(a.to_string(), before);
+    assert_eq!(low.format(&a), "3.340e_1");
+    assert_eq!(high.format(&b), "3.33333333333333333333333333333e_1");
+    // Formatting uses the output precision of the interpreter doing it
+    assert_eq!(low.format(&b), "3.333e_1");
+    assert_eq!(high.format(&a), "3.33984375000000000000000000000e_1");
+    assert_eq!(a.to_string(), before);
+}
+
+#[test]
+fn interpreters_on_other_threads_do_not_interfere() {
+    let handles = [(8, "3.340e_1"), (60, "3.333e_1")].map(|(bits, expected)| {
+        std::thread::spawn(move || {
+            let mut arn = interpreter(bits, 4);
+            for _ in 0..100 {
+                assert_eq!(arn.eval("1/3").unwrap().to_string(), expected);
+            }
+        })
+    });
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+fn precision_can_change_between_programs() {
+    let mut arn = interpreter(50, 4);
+    let root = arn.eval(":/2").unwrap();
+    assert_eq!(arn.format(&root), "1.414");
+
+    arn.set_precision(Precision {
+        float: 100,
+        output: 20,
+    })
+    .unwrap();
+    let root = arn.eval(":/2").unwrap();
+    assert_eq!(arn.format(&root), "1.4142135623730950488");
+    assert_eq!(arn.options().float_precision, 100);
+    assert_eq!(arn.options().output_precision, 20);
+}
+
+#[test]
+fn invalid_precisions_are_errors() {
+    let error = "Invalid input: `0` is not a valid float precision";
+    let made = Interpreter::new(Options {
+        float_precision: 0,
+        ..Options::default()
+    });
+    assert_eq!(made.err().map(|e| e.to_string()).as_deref(), Some(error));
+
+    let mut arn = interpreter(50, 4);
+    let set = arn.set_precision(Precision {
+        float: 0,
+        output: 20,
+    });
+    assert_eq!(set.err().map(|e| e.to_string()).as_deref(), Some(error));
+    assert_eq!(
+        arn.precision(),
+        Precision {
+            float: 50,
+            output: 4
+        }
+    );
+}
+
+#[test]
+fn block_precision_is_restored() {
+    let mut arn = interpreter(50, 4);
+    arn.eval("8:p(1/3)").unwrap();
+    assert_eq!(arn.precision().float, 50);
+    assert_eq!(arn.eval("(1/3)=(50:p(1/3))").unwrap().to_string(), "1");
+}
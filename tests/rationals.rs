@@ -0,0 +1,59 @@
+mod common;
+
+use arn_language::{Interpreter, Options, RationalMode};
+use common::run_in;
+
+#[test]
+fn division_stays_exact() {
+    for (program, expected) in [
+        ("1/3", "1/3"),
+        ("6/3", "2"),
+        ("1/3+1/6", "1/2"),
+        ("_2/6", "_1/3"),
+        ("(1/3)*3", "1"),
+        ("(2/3)^3", "8/27"),
+        (":/(4/9)", "2/3"),
+        (":-3", "3/2"),
+        ("(7/2)%1", "1/2"),
+        ("sH?3", "1/16"),
+    ] {
+        assert_eq!(
+            run_in(program, RationalMode::Fraction),
+            expected,
+            "{}",
+            program
+        );
+    }
+}
+
+#[test]
+fn fractions_compare_exactly() {
+    for (program, expected) in [
+        ("(1/3)=(2/6)", "1"),
+        ("(1/3)<(1/2)", "1"),
+        ("5e_1=(1/2)", "1"),
+        ("3e_1<(1/3)", "1"),
+    ] {
+        assert_eq!(
+            run_in(program, RationalMode::Fraction),
+            expected,
+            "{}",
+            program
+        );
+    }
+}
+
+#[test]
+fn decimal_mode_only_changes_output() {
+    assert_eq!(run_in("5/2", RationalMode::Decimal), "2.5");
+    assert_eq!(run_in("(1/3)*3", RationalMode::Decimal), "1");
+    assert_eq!(run_in("5/2", RationalMode::Off), "2.5");
+}
+
+#[test]
+fn mode_survives_reset() {
+    let mut arn = Interpreter::new(Options::default()).unwrap();
+    arn.set_rational_mode(RationalMode::Fraction);
+    arn.reset().unwrap();
+    assert_eq!(arn.eval("2/4").unwrap().to_string(), "1/2");
+}
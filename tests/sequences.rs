@@ -0,0 +1,105 @@
+mod common;
+
+use arn_language::{Interpreter, Options};
+use common::run;
+
+#[test]
+fn operators_stay_lazy_on_infinite_sequences() {
+    for (program, expected) in [
+        ("(sO@{*2}):#4", "2\n6\n10\n14\n"),
+        ("($ {%3=0} sO):#3", "3\n9\n15\n"),
+        ("(sE z sO)?1", "4\n3\n"),
+        ("[1 2 3]z sE", "1 2 \n2 4 \n3 6 \n"),
+        ("$:{>10}sE", "1"),
+        (":${<10}sF", "1\n1\n2\n3\n5\n8\n"),
+        ("(($ {%2} sF)@{*10}):#3", "10\n10\n30\n"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn operators_that_need_every_value_fail() {
+    let mut arn = Interpreter::new(Options::default()).unwrap();
+    for program in [":<sE", "/:{+1}sE", "#>sO", "sE z[1 2]"] {
+        assert!(arn.eval(program).is_err(), "{}", program);
+    }
+}
+
+#[test]
+fn terms_are_shared_between_uses() {
+    let mut arn = Interpreter::new(Options::default()).unwrap();
+    arn.eval("sF?200").unwrap();
+
+    let sf = arn.env().borrow().get_var("sF").unwrap().literal_array();
+    assert_eq!(
+        sf.cached(200).unwrap().to_string(),
+        "453973694165307953197296969697410619233826"
+    );
+    assert!(sf.cached(201).is_none());
+}
+
+#[test]
+fn reversing_computes_each_term_once() {
+    for (program, expected) in [
+        (".<[1{+3}->5]", "13\n10\n7\n4\n1\n"),
+        (".<(1=>4)", "4\n3\n2\n1\n"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn blocks_can_use_the_index_of_the_term() {
+    for (program, expected) in [
+        ("[{:*n}]:#5", "0\n1\n4\n9\n16\n"),
+        ("[{N}->4]", "1\n2\n3\n4\n"),
+        ("[0{+n}]:#5", "0\n1\n3\n6\n10\n"),
+        ("[1{*N}]?5", "720"),
+        // A user defined `n` shadows the index
+        ("n:={7}\n[{n}]?3", "7"),
+        ("n:={7}\n[{n}]?3\nn", "7"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn the_index_does_not_hide_variables() {
+    for (program, expected) in [
+        ("n:=7\n[1{+n}]:#3", "1\n8\n15\n"),
+        ("N:=2\n[1{*N}]:#4", "1\n2\n4\n8\n"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn the_index_is_the_argument_of_the_block() {
+    for (program, expected) in [
+        ("[{n:*}]:#5", "0\n1\n4\n9\n16\n"),
+        ("[{:*}]:#5", "0\n1\n4\n9\n16\n"),
+        ("[v{:*v}]:#4", "0\n1\n4\n9\n"),
+        ("[1 v{+v}]:#5", "1\n2\n4\n7\n11\n"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn builtin_sequences() {
+    for (program, expected) in [
+        ("sP:#6", "2\n3\n5\n7\n11\n13\n"),
+        ("sS:#4", "1\n4\n9\n16\n"),
+        ("sT:#4", "1\n3\n6\n10\n"),
+        ("sC:#6", "1\n1\n2\n5\n14\n42\n"),
+        ("sf:#5", "1\n1\n2\n6\n24\n"),
+        ("s2:#5", "1\n2\n4\n8\n16\n"),
+        ("sL:#6", "2\n1\n3\n4\n7\n11\n"),
+        ("sP?9999", "104729"),
+        ("s2?100", "1267650600228229401496703205376"),
+        ("($ {%4=1} sP):#3", "5\n13\n17\n"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn negative_indices_count_from_the_end() {
+    for (program, expected) in [
+        ("[1 2 3]?_1", "3"),
+        ("[1 2 3]?_3", "1"),
+        (
+            "[1 2 3]?_4",
+            "error: Index out of range: no value at index _4",
+        ),
+        (
+            "sE?_1",
+            "error: Infinite sequence: `?` cannot be used on an infinite sequence",
+        ),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn slices_match_python() {
+    for (program, expected) in [
+        ("\"abcdef\":?[1 _1]", "bcde"),
+        ("\"abcdef\":?[c c _1]", "fedcba"),
+        ("\"abcdef\":?[_2 c _2]", "eca"),
+        ("\"abcdef\":?[10 20]", ""),
+        ("\"abcdef\"?<_10", ""),
+        ("[1 2 3 4 5 6]:?[c c 2]", "1\n3\n5\n"),
+        ("[1 2 3 4 5 6]:?[4 1 _1]", "5\n4\n3\n"),
+        ("[1 2 3]?%0", "error: Type error: `?%` cannot step by 0"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn infinite_sequences_are_sliced_lazily() {
+    for (program, expected) in [
+        ("sO?>3:#2", "7\n9\n"),
+        ("sO?%3:#3", "1\n7\n13\n"),
+        ("sO:?[1 7 2]", "3\n7\n11\n"),
+        ("sO?<3", "1\n3\n5\n"),
+        ("(.{sO)?0", "3"),
+        (
+            "sO?<_1",
+            "error: Infinite sequence: `?<` cannot count back from the end of an infinite sequence",
+        ),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn behead_and_drop_reject_empty_input() {
+    assert_eq!(
+        run(".{c"),
+        "error: Index out of range: `.{` cannot be used on an empty sequence"
+    );
+    assert_eq!(
+        run(".}[]"),
+        "error: Index out of range: `.}` cannot be used on an empty sequence"
+    );
+    assert_eq!(run(".}[1 2 3]"), "1\n2\n");
+}
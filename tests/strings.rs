@@ -0,0 +1,39 @@
+mod common;
+
+use arn_language::utils::compress::{is_packed, pack, unpack};
+use common::run;
+
+#[test]
+fn string_operators_work_on_characters() {
+    for (program, expected) in [
+        ("|:\"añob日本\"", "año\n本日b\n"),
+        ("#\"日本語\"", "3"),
+        ("\"héllo\"?1", "é"),
+        ("\"日本語\":?[c c _1]", "語本日"),
+        ("\"ü\"^3", "üüü"),
+    ] {
+        assert_eq!(run(program), expected, "{}", program);
+    }
+}
+
+#[test]
+fn case_mapping_is_unicode_aware() {
+    assert_eq!(run(":^\"straße\""), "STRASSE");
+    assert_eq!(run(":v\"ÉCOLE\""), "école");
+    assert_eq!(run(":^\"ἀθήνη\""), "ἈΘΉΝΗ");
+}
+
+#[test]
+fn only_ascii_programs_are_packed() {
+    assert_eq!(pack("\"é\""), None);
+    let packed = pack("\"Hello\"").unwrap();
+    assert_eq!(unpack(&packed), "\"Hello\"");
+    assert!(is_packed(&packed));
+}
+
+#[test]
+fn non_ascii_strings_are_not_mistaken_for_packed_code() {
+    for program in ["\"héllo\"", ":^\"日本\"", "\"a b\"|\"ñ\""] {
+        assert!(!is_packed(program), "{}", program);
+    }
+}